@@ -7,15 +7,13 @@ use minime::{editor::keybindings::NormalKeybinding, editor::Editor, Result, rend
 //      Write something cool!
 //      hello there
 //      how are you?
-//      [examples\basic.rs:14] term.read_multiline() = Ok(
-//          "hello there\nhow are you?",
-//      )
+//      [examples/basic.rs:19:5] term.contents() = "hello there\nhow are you?"
 
 fn main() -> Result<()> {
     println!("Write something cool!");
     // Build the prompt.
     let mut term = Editor::default();
-    term.read(NormalKeybinding, DefaultRenderer::default())?;
+    term.read(NormalKeybinding::default(), DefaultRenderer::default())?;
     dbg!(term.contents());
     Ok(())
 }
@@ -2,7 +2,7 @@ use minime::{
     editor::{keybindings::NormalKeybinding, Editor},
     renderer::{
         full::CrosstermRenderer,
-        styles::classic::{ClassicFooter, ClassicGutter, ClassicHeader},
+        styles::classic,
     },
     Result,
 };
@@ -14,15 +14,11 @@ fn main() -> Result<()> {
 
     let renderer = CrosstermRenderer::render_to(&mut lock)
         .max_height(Some(10))
-        .margin(ClassicGutter)
-        .header(ClassicHeader {
-            message: "Enter on the last line or Esc to submit your input!",
-        })
-        .footer(ClassicFooter);
+        .style(classic::bundle("Ctrl+D or Esc to submit your input!"));
 
     // Print out some prompt using styling options.
     let mut term = Editor::default();
-    term.read(NormalKeybinding, renderer)?;
+    term.read(NormalKeybinding::default(), renderer)?;
     dbg!(term.contents());
     Ok(())
 }
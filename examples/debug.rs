@@ -17,7 +17,7 @@ fn main() -> Result<()> {
         .header(FancyHeader {
             message: "Debug keybindings for keybinding development",
         })
-        .margin(FancyGutter)
+        .margin(FancyGutter::default())
         .footer(FancyFooter);
 
     // Print out some prompt using styling options.
@@ -0,0 +1,27 @@
+use minime::{
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::{string::StringRenderer, styles::classic::ClassicGutter},
+    Result,
+};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn main() -> Result<()> {
+    // Type two lines, then fix a typo on the first line.
+    let mut events: Vec<Event> = "helo\nworld".chars().map(|c| key(KeyCode::Char(c))).collect();
+    events.extend([KeyCode::Up, KeyCode::End, KeyCode::Left].iter().map(|&code| key(code)));
+    events.push(key(KeyCode::Char('l')));
+
+    let mut frame = Vec::new();
+    let renderer = StringRenderer::render_to(&mut frame).margin(ClassicGutter::default());
+
+    let mut term = Editor::default();
+    let signal = term.drive(NormalKeybinding::default(), renderer, events)?;
+
+    println!("{}", String::from_utf8_lossy(&frame));
+    dbg!(signal, term.contents());
+    Ok(())
+}
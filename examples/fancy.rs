@@ -2,7 +2,7 @@ use minime::{
     editor::{keybindings::NormalKeybinding, Editor},
     renderer::{
         full::CrosstermRenderer,
-        styles::fancy::{FancyFooter, FancyGutter, FancyHeader},
+        styles::fancy,
     },
     Result,
 };
@@ -14,15 +14,11 @@ fn main() -> Result<()> {
 
     let renderer = CrosstermRenderer::render_to(&mut lock)
         .max_height(Some(10))
-        .header(FancyHeader {
-            message: "Type something :)",
-        })
-        .margin(FancyGutter)
-        .footer(FancyFooter);
+        .style(fancy::bundle("Type something :)"));
 
     // Print out some prompt using styling options.
     let mut term = Editor::default();
-    term.read(NormalKeybinding, renderer)?;
+    term.read(NormalKeybinding::default(), renderer)?;
     dbg!(term.contents());
     Ok(())
 }
@@ -0,0 +1,41 @@
+use std::ops::Range;
+
+use minime::{
+    crossterm::style::Color,
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::{full::CrosstermRenderer, styles::classic::ClassicGutter},
+    Result,
+};
+
+// Colors the numbers of each line.
+fn numbers(line: &str) -> Vec<(Range<usize>, Color)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (col, c) in line.chars().chain(Some(' ')).enumerate() {
+        match (start, c.is_ascii_digit()) {
+            (None, true) => start = Some(col),
+            (Some(s), false) => {
+                spans.push((s..col, Color::Yellow));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+fn main() -> Result<()> {
+    // Redirect our output to stdout (default).
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+
+    let renderer = CrosstermRenderer::render_to(&mut lock)
+        .max_height(Some(10))
+        .margin(ClassicGutter::default())
+        .highlighter(numbers);
+
+    let mut term = Editor::default();
+    term.read(NormalKeybinding::default(), renderer)?;
+    dbg!(term.contents());
+    Ok(())
+}
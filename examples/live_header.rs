@@ -0,0 +1,31 @@
+use minime::{
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::{
+        full::CrosstermRenderer,
+        styles::classic::{ClassicGutter, ClassicHeader},
+    },
+    Result,
+};
+
+// Shows the position of the focus in the header, updated on every draw.
+
+fn main() -> Result<()> {
+    // Redirect our output to stdout (default).
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+
+    let renderer = CrosstermRenderer::render_to(&mut lock)
+        .max_height(Some(10))
+        .margin(ClassicGutter::default())
+        .header(ClassicHeader {
+            message: |data: &Editor| {
+                let focus = data.selection.focus;
+                format!("Ln {}, Col {}", focus.ln + 1, focus.col + 1)
+            },
+        });
+
+    let mut term = Editor::default();
+    term.read(NormalKeybinding::default(), renderer)?;
+    dbg!(term.contents());
+    Ok(())
+}
@@ -13,6 +13,6 @@ fn main() -> Result<()> {
 
     // Print out some prompt using styling options.
     let mut term = Editor::default();
-    dbg!(term.read(NormalKeybinding, renderer)?);
+    dbg!(term.read(NormalKeybinding::default(), renderer)?);
     Ok(())
 }
@@ -0,0 +1,33 @@
+use minime::{
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::{
+        full::CrosstermRenderer,
+        styles::classic::{ClassicFooter, ClassicGutter},
+    },
+    Result,
+};
+
+// Rejects input that is only whitespace.
+fn not_empty(contents: &str) -> std::result::Result<(), String> {
+    if contents.trim().is_empty() {
+        Err("Write something first!".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn main() -> Result<()> {
+    // Redirect our output to stdout (default).
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+
+    let renderer = CrosstermRenderer::render_to(&mut lock)
+        .max_height(Some(10))
+        .margin(ClassicGutter::default())
+        .footer(ClassicFooter);
+
+    let mut term = Editor::default().with_validator(not_empty);
+    let contents = term.read(NormalKeybinding::default(), renderer)?;
+    dbg!(contents);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use minime::{
+    editor::{keybindings::VimKeybinding, Editor},
+    renderer::{
+        full::CrosstermRenderer,
+        styles::fancy::{FancyFooter, FancyGutter, FancyHeader},
+    },
+    Result,
+};
+
+// Modal editing example.
+//
+// Starts in normal mode. Press `i` to insert text,
+// `Esc` to return to normal mode, and `:` to submit.
+
+fn main() -> Result<()> {
+    // Redirect our output to stdout (default).
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+
+    let renderer = CrosstermRenderer::render_to(&mut lock)
+        .max_height(Some(10))
+        .header(FancyHeader {
+            message: "Press i to insert, Esc for normal mode and : to submit",
+        })
+        .margin(FancyGutter::relative())
+        .footer(FancyFooter);
+
+    let mut term = Editor::default();
+    term.read(VimKeybinding::default(), renderer)?;
+    dbg!(term.contents());
+    Ok(())
+}
@@ -0,0 +1,33 @@
+/// Clipboard used by the copy, cut and paste actions.
+pub trait Clipboard {
+    /// Get the text on the clipboard, if any.
+    fn get(&mut self) -> Option<String>;
+
+    /// Put text on the clipboard.
+    fn set(&mut self, text: String);
+}
+
+/// Clipboard of the system, through `arboard`.
+#[cfg(feature = "unstable")]
+pub struct SystemClipboard(arboard::Clipboard);
+
+#[cfg(feature = "unstable")]
+impl SystemClipboard {
+    /// Connect to the clipboard of the system, or `None`
+    /// if it is not available, such as over SSH.
+    pub fn new() -> Option<Self> {
+        arboard::Clipboard::new().ok().map(Self)
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl Clipboard for SystemClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.0.get_text().ok()
+    }
+
+    fn set(&mut self, text: String) {
+        // The text is still marked as copied for the renderer.
+        let _ = self.0.set_text(text);
+    }
+}
@@ -0,0 +1,182 @@
+use super::{Editor, LineStatus};
+
+/// Largest number of cells in the table of a line diff,
+/// past which the changed lines are paired up in order.
+const MAX_TABLE: usize = 1 << 20;
+
+impl Editor {
+    /// Set the text that `line_status` compares the lines against,
+    /// such as the content before editing, for review.
+    pub fn set_baseline(&mut self, baseline: &str) {
+        let baseline = baseline.replace("\r\n", "\n");
+        self.baseline = Some(baseline.split('\n').map(String::from).collect());
+        self.diff.take();
+    }
+
+    /// Stop comparing the lines against a baseline.
+    pub fn clear_baseline(&mut self) {
+        self.baseline = None;
+        self.diff.take();
+    }
+
+    /// Get how the line given an index differs from the baseline.
+    /// Every line is unchanged if there is no baseline.
+    pub fn line_status(&self, line_idx: usize) -> LineStatus {
+        let baseline = match &self.baseline {
+            Some(baseline) => baseline,
+            None => return LineStatus::Unchanged,
+        };
+        let mut diff = self.diff.borrow_mut();
+        let statuses = diff.get_or_insert_with(|| {
+            let lines: Vec<_> = self.lines().collect();
+            diff_lines(baseline, &lines)
+        });
+        statuses
+            .get(line_idx)
+            .copied()
+            .unwrap_or(LineStatus::Unchanged)
+    }
+}
+
+/// Get the status of every current line compared to the old lines.
+fn diff_lines(old: &[String], new: &[impl AsRef<str>]) -> Vec<LineStatus> {
+    // Only diff the lines between the common start and end.
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a.as_str() == b.as_ref())
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.as_str() == b.as_ref())
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut statuses = vec![LineStatus::Unchanged; new.len()];
+    let mut hunk = Hunk::default();
+    if old_mid.len() * new_mid.len() > MAX_TABLE {
+        hunk.removed = old_mid.len();
+        hunk.added = (prefix..prefix + new_mid.len()).collect();
+        hunk.close(&mut statuses, prefix + new_mid.len());
+        return statuses;
+    }
+
+    // Lengths of the longest common subsequences of the remaining lines.
+    let width = new_mid.len() + 1;
+    let mut table = vec![0; (old_mid.len() + 1) * width];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            table[i * width + j] = if old_mid[i] == new_mid[j].as_ref() {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j].as_ref() {
+            hunk.close(&mut statuses, prefix + j);
+            i += 1;
+            j += 1;
+        } else if j < new_mid.len()
+            && (i == old_mid.len() || table[i * width + j + 1] >= table[(i + 1) * width + j])
+        {
+            hunk.added.push(prefix + j);
+            j += 1;
+        } else {
+            hunk.removed += 1;
+            i += 1;
+        }
+    }
+    hunk.close(&mut statuses, prefix + new_mid.len());
+    statuses
+}
+
+/// Lines removed and added between two unchanged lines.
+#[derive(Default)]
+struct Hunk {
+    removed: usize,
+    added: Vec<usize>,
+}
+
+impl Hunk {
+    /// Mark the lines of the hunk, which ends before the line given an index.
+    /// Added lines that replace removed lines are marked as modified.
+    fn close(&mut self, statuses: &mut [LineStatus], next: usize) {
+        for (n, &ln) in self.added.iter().enumerate() {
+            statuses[ln] = if n < self.removed {
+                LineStatus::Modified
+            } else {
+                LineStatus::Added
+            };
+        }
+        if self.added.is_empty() && self.removed > 0 {
+            // Mark the line after the removed lines, or the last line.
+            let ln = next.min(statuses.len().saturating_sub(1));
+            if let Some(status) = statuses.get_mut(ln) {
+                *status = LineStatus::RemovedAbove;
+            }
+        }
+        *self = Hunk::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::selection::Cursor;
+
+    fn statuses(baseline: &str, contents: &str) -> Vec<LineStatus> {
+        let mut editor = Editor::from(contents);
+        editor.set_baseline(baseline);
+        (0..editor.line_count())
+            .map(|ln| editor.line_status(ln))
+            .collect()
+    }
+
+    #[test]
+    fn added_lines() {
+        use LineStatus::*;
+        assert_eq!(statuses("a\nc", "a\nb\nc"), [Unchanged, Added, Unchanged]);
+        assert_eq!(statuses("a", "a\nb\nc"), [Unchanged, Added, Added]);
+    }
+
+    #[test]
+    fn modified_lines() {
+        use LineStatus::*;
+        assert_eq!(
+            statuses("a\nb\nc", "a\nB\nc"),
+            [Unchanged, Modified, Unchanged]
+        );
+        // Lines added beyond the removed ones are added.
+        assert_eq!(
+            statuses("a\nb\nd", "a\nB\nC\nd"),
+            [Unchanged, Modified, Added, Unchanged]
+        );
+    }
+
+    #[test]
+    fn deleted_lines() {
+        use LineStatus::*;
+        assert_eq!(statuses("a\nb\nc\nd", "a\nd"), [Unchanged, RemovedAbove]);
+        // Lines deleted at the end mark the last line.
+        assert_eq!(statuses("a\nb\nc", "a\nb"), [Unchanged, RemovedAbove]);
+    }
+
+    #[test]
+    fn status_follows_edits() {
+        let mut editor = Editor::from("ab\ncd");
+        editor.set_baseline("ab\ncd");
+        assert_eq!(editor.line_status(1), LineStatus::Unchanged);
+        editor.selection.focus = Cursor { ln: 1, col: 2 };
+        editor.type_char('e');
+        assert_eq!(editor.line_status(1), LineStatus::Modified);
+        editor.clear_baseline();
+        assert_eq!(editor.line_status(1), LineStatus::Unchanged);
+    }
+}
@@ -0,0 +1,157 @@
+use ropey::Rope;
+
+use super::selection::Selection;
+
+/// A single change to the buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Edit {
+    /// Character index of the change.
+    pub idx: usize,
+    /// Text removed at the index.
+    pub removed: String,
+    /// Text inserted at the index.
+    pub inserted: String,
+}
+
+impl Edit {
+    fn apply(&self, buf: &mut Rope) {
+        buf.remove(self.idx..self.idx + self.removed.chars().count());
+        buf.insert(self.idx, &self.inserted);
+    }
+
+    fn revert(&self, buf: &mut Rope) {
+        buf.remove(self.idx..self.idx + self.inserted.chars().count());
+        buf.insert(self.idx, &self.removed);
+    }
+
+    fn bytes(&self) -> usize {
+        self.removed.len() + self.inserted.len()
+    }
+
+    /// Check if this edit types a single character.
+    fn is_typing(&self) -> bool {
+        self.removed.is_empty() && self.inserted.chars().count() == 1
+    }
+}
+
+/// A group of edits that are undone and redone together.
+#[derive(Debug, Clone)]
+struct Group {
+    edits: Vec<Edit>,
+    before: Selection,
+    after: Selection,
+}
+
+impl Group {
+    fn bytes(&self) -> usize {
+        self.edits.iter().map(Edit::bytes).sum()
+    }
+
+    /// Check if the next group continues typing a word right after this
+    /// group, in which case both are undone together.
+    fn coalesces(&self, next: &Group) -> bool {
+        match (self.edits.last(), next.edits.as_slice()) {
+            (Some(last), [edit]) => {
+                self.edits.iter().all(Edit::is_typing)
+                    && edit.is_typing()
+                    && !edit.inserted.chars().all(char::is_whitespace)
+                    && last.idx + 1 == edit.idx
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Undo and redo history of the editor.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct History {
+    undo: Vec<Group>,
+    redo: Vec<Group>,
+    pending: Option<Group>,
+    max_groups: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl History {
+    /// Set the maximum number of undo steps and retained bytes.
+    pub fn set_limits(&mut self, max_groups: Option<usize>, max_bytes: Option<usize>) {
+        self.max_groups = max_groups;
+        self.max_bytes = max_bytes;
+        self.evict();
+    }
+
+    /// Forget all history.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+    }
+
+    /// Record an edit into the pending group.
+    pub fn record(&mut self, edit: Edit, before: Selection) {
+        self.pending
+            .get_or_insert_with(|| Group {
+                edits: Vec::new(),
+                before,
+                after: before,
+            })
+            .edits
+            .push(edit);
+    }
+
+    /// Close the pending group, making it an undo step.
+    pub fn commit(&mut self, after: Selection) {
+        if let Some(mut group) = self.pending.take() {
+            group.after = after;
+            match self.undo.last_mut() {
+                Some(last) if last.coalesces(&group) => {
+                    last.edits.extend(group.edits);
+                    last.after = group.after;
+                }
+                _ => self.undo.push(group),
+            }
+            self.redo.clear();
+            self.evict();
+        }
+    }
+
+    /// Undo the last step, returning the selection before it.
+    pub fn undo(&mut self, buf: &mut Rope) -> Option<Selection> {
+        let group = self.undo.pop()?;
+        group.edits.iter().rev().for_each(|edit| edit.revert(buf));
+        let selection = group.before;
+        self.redo.push(group);
+        Some(selection)
+    }
+
+    /// Redo the last undone step, returning the selection after it.
+    pub fn redo(&mut self, buf: &mut Rope) -> Option<Selection> {
+        let group = self.redo.pop()?;
+        group.edits.iter().for_each(|edit| edit.apply(buf));
+        let selection = group.after;
+        self.undo.push(group);
+        Some(selection)
+    }
+
+    /// Evict the oldest steps until the history is within its limits,
+    /// always keeping the newest step.
+    fn evict(&mut self) {
+        if let Some(max_groups) = self.max_groups {
+            let excess = self.undo.len().saturating_sub(max_groups.max(1));
+            self.undo.drain(..excess);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            let mut bytes: usize = self.undo.iter().map(Group::bytes).sum();
+            let mut excess = 0;
+            let oldest = self.undo.len().saturating_sub(1);
+            for group in &self.undo[..oldest] {
+                if bytes <= max_bytes {
+                    break;
+                }
+                bytes -= group.bytes();
+                excess += 1;
+            }
+            self.undo.drain(..excess);
+        }
+    }
+}
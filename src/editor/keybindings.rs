@@ -1,127 +1,759 @@
-use crate::{editor::Editor, Result};
+use crate::{
+    editor::{
+        clipboard::Clipboard,
+        keymap::{Action, KeyMap},
+        selection::Cursor,
+        Editor,
+    },
+    error::Error,
+    Result,
+};
 
-use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+};
+
+/// Outcome of processing an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Keep reading input.
+    Continue,
+    /// Stop reading input, accepting the content.
+    Submit,
+    /// Stop reading input, discarding the content.
+    Cancel,
+}
 
 /// Generic keybinding trait.
+///
+/// Keybindings are driven mutably, so they may keep state
+/// between events, such as modes or pending key chords.
 pub trait Keybinding {
     /// Read a key from the environment and act upon the editor.
-    fn read(&self, editor: &mut Editor) -> Result<bool>;
+    fn read(&mut self, editor: &mut Editor) -> Result<Signal> {
+        let event = read().map_err(Error::Terminal)?;
+        editor.begin_event(&event);
+        self.process_event(editor, event)
+    }
+
+    /// Act upon the editor given an event.
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal>;
+
+    /// Check if digits pressed now are a count prefix for the next command,
+    /// such as in a command mode, rather than text. False by default.
+    fn accepts_count(&self) -> bool {
+        false
+    }
+}
+
+impl<K: Keybinding + ?Sized> Keybinding for &mut K {
+    fn read(&mut self, editor: &mut Editor) -> Result<Signal> {
+        (**self).read(editor)
+    }
+
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal> {
+        (**self).process_event(editor, event)
+    }
+
+    fn accepts_count(&self) -> bool {
+        (**self).accepts_count()
+    }
 }
 
 /// Default keybindings for the editor.
-pub struct NormalKeybinding;
+pub struct NormalKeybinding {
+    list_markers: Vec<ListMarker>,
+    prompt: Option<Prompt>,
+    hard_tabs: bool,
+    keymap: KeyMap,
+    comment_prefix: String,
+    read_only_enter_submits: bool,
+    clipboard: Option<Box<dyn Clipboard>>,
+    dragging_scrollbar: bool,
+}
 
-impl Keybinding for NormalKeybinding {
-    fn read(&self, editor: &mut Editor) -> Result<bool> {
-        let key_event = read()?;
-        match key_event {
-            Event::Key(k) => Self::process_key_event(editor, k),
-            _ => Ok(true),
+impl Default for NormalKeybinding {
+    /// Keybindings that copy to the system clipboard when it is available
+    /// and the `unstable` feature is enabled, or to the internal register.
+    fn default() -> Self {
+        #[cfg(feature = "unstable")]
+        let clipboard = super::clipboard::SystemClipboard::new()
+            .map(|clipboard| Box::new(clipboard) as Box<dyn Clipboard>);
+        #[cfg(not(feature = "unstable"))]
+        let clipboard = None;
+
+        Self {
+            list_markers: Vec::new(),
+            prompt: None,
+            hard_tabs: false,
+            keymap: KeyMap::default(),
+            comment_prefix: "//".to_string(),
+            read_only_enter_submits: false,
+            clipboard,
+            dragging_scrollbar: false,
         }
     }
 }
 
-impl NormalKeybinding {
-    fn process_key_event(editor: &mut Editor, event: KeyEvent) -> Result<bool> {
-        let code = event.code;
-        let ln_count = editor.line_count();
-        // let mut cursor = editor.cursor();
-        let shifted = event.modifiers.contains(KeyModifiers::SHIFT);
-        let alt = event.modifiers.contains(KeyModifiers::ALT);
-        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+/// State of an active prompt, which receives keys until
+/// it is confirmed with `Enter` or dismissed with `Esc`.
+enum Prompt {
+    /// Incremental search, starting from the origin.
+    Search { query: String, origin: Cursor },
+    /// Line number to jump to.
+    GoToLine { input: String },
+}
 
-        match code {
-            KeyCode::Down => editor.move_down(shifted),
-            KeyCode::Up => editor.move_up(shifted),
-            KeyCode::Left => editor.move_left(shifted),
-            KeyCode::Right => editor.move_right(shifted),
-
-            KeyCode::PageDown => editor.move_to_bottom(),
-            KeyCode::PageUp => editor.move_to_top(),
-            KeyCode::Home => {
-                let leading_spaces = editor
-                    .curr_ln_chars()
-                    .take_while(|c| c.is_whitespace())
-                    .count();
-                if editor.selection.focus.col == leading_spaces {
-                    editor.move_to_col(0, shifted);
+/// A list marker that is continued on the next line when pressing Enter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListMarker {
+    /// A literal bullet prefix, such as `"- "`.
+    Bullet(String),
+    /// A numbered marker, such as `"1. "`, which is incremented.
+    Numbered,
+}
+
+impl ListMarker {
+    /// Markdown-like `- `, `* ` and `1. ` markers.
+    pub fn defaults() -> Vec<ListMarker> {
+        vec![
+            ListMarker::Bullet(String::from("- ")),
+            ListMarker::Bullet(String::from("* ")),
+            ListMarker::Numbered,
+        ]
+    }
+
+    /// Match the marker at the start of the text, returning the length
+    /// of the marker and the marker for the next line.
+    fn matches(&self, text: &str) -> Option<(usize, String)> {
+        match self {
+            ListMarker::Bullet(bullet) => {
+                if text.starts_with(bullet.as_str()) {
+                    Some((bullet.chars().count(), bullet.clone()))
                 } else {
-                    editor.move_to_col(leading_spaces, shifted);
+                    None
+                }
+            }
+            ListMarker::Numbered => {
+                let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 || !text[digits..].starts_with(". ") {
+                    return None;
+                }
+                let n: usize = text[..digits].parse().ok()?;
+                Some((digits + 2, format!("{}. ", n.checked_add(1)?)))
+            }
+        }
+    }
+}
+
+impl Keybinding for NormalKeybinding {
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal> {
+        match event {
+            Event::Key(k) if k.kind != KeyEventKind::Release => self.process_key_event(editor, k),
+            Event::Mouse(m) => {
+                match m.kind {
+                    MouseEventKind::ScrollUp => editor.scroll_by(-SCROLL_LINES),
+                    MouseEventKind::ScrollDown => editor.scroll_by(SCROLL_LINES),
+                    // Clicking the scrollbar moves the thumb there,
+                    // and dragging keeps moving it until released.
+                    MouseEventKind::Down(MouseButton::Left)
+                        if editor.on_scrollbar(m.column, m.row) =>
+                    {
+                        self.dragging_scrollbar = true;
+                        editor.scroll_to_scrollbar_row(m.row);
+                    }
+                    MouseEventKind::Drag(MouseButton::Left) if self.dragging_scrollbar => {
+                        editor.scroll_to_scrollbar_row(m.row);
+                    }
+                    MouseEventKind::Up(_) => self.dragging_scrollbar = false,
+                    _ => {}
                 }
+                Ok(Signal::Continue)
             }
-            KeyCode::End => editor.move_to_line_end(shifted),
+            Event::Paste(text) => {
+                self.paste(editor, &text);
+                Ok(Signal::Continue)
+            }
+            _ => Ok(Signal::Continue),
+        }
+    }
+}
+
+/// Lines scrolled by a notch of the mouse wheel.
+const SCROLL_LINES: isize = 3;
+
+impl NormalKeybinding {
+    /// Continue list markers when pressing Enter on a list item.
+    /// Pressing Enter on an empty list item removes the marker instead.
+    pub fn with_list_markers(self, list_markers: Vec<ListMarker>) -> Self {
+        Self {
+            list_markers,
+            ..self
+        }
+    }
 
-            KeyCode::Backspace => editor.backspace(),
-            KeyCode::Char('h') if control => editor.backspace(),
-            KeyCode::Delete => editor.delete(),
+    /// Set the mapping of keys to actions.
+    pub fn with_keymap(self, keymap: KeyMap) -> Self {
+        Self { keymap, ..self }
+    }
+
+    /// Insert a literal tab character when pressing Tab,
+    /// instead of spaces up to the next tab stop.
+    pub fn with_hard_tabs(self, hard_tabs: bool) -> Self {
+        Self { hard_tabs, ..self }
+    }
+
+    /// Set the clipboard used to copy, cut and paste.
+    pub fn with_clipboard(self, clipboard: impl Clipboard + 'static) -> Self {
+        Self {
+            clipboard: Some(Box::new(clipboard)),
+            ..self
+        }
+    }
+
+    /// Copy, cut and paste with the internal register of the editor
+    /// instead of a clipboard.
+    pub fn without_clipboard(self) -> Self {
+        Self {
+            clipboard: None,
+            ..self
+        }
+    }
 
-            KeyCode::F(12) => {
-                editor.altscreen = !editor.altscreen;
+    /// Set the prefix inserted when toggling line comments, `//` by default.
+    pub fn with_comment_prefix(self, comment_prefix: impl Into<String>) -> Self {
+        Self {
+            comment_prefix: comment_prefix.into(),
+            ..self
+        }
+    }
+
+    /// Set whether Enter on the last line submits when the line is empty,
+    /// rather than always inserting a new line. Ctrl+Enter and Ctrl+D
+    /// submit either way.
+    pub fn with_blank_line_submit(self, blank_line_submit: bool) -> Self {
+        let action = if blank_line_submit {
+            Action::NewlineOrSubmit
+        } else {
+            Action::Newline
+        };
+        Self {
+            keymap: self.keymap.bind(KeyCode::Enter, KeyModifiers::NONE, action),
+            ..self
+        }
+    }
+
+    /// Set whether Esc cancels the input, discarding the content,
+    /// rather than submitting it.
+    pub fn with_esc_cancels(self, esc_cancels: bool) -> Self {
+        let action = if esc_cancels {
+            Action::Cancel
+        } else {
+            Action::Submit
+        };
+        Self {
+            keymap: self.keymap.bind(KeyCode::Esc, KeyModifiers::NONE, action),
+            ..self
+        }
+    }
+
+    /// Set whether Ctrl+C cancels the input, discarding the content,
+    /// rather than copying.
+    ///
+    /// Raw mode stops Ctrl+C from interrupting the program, so by default
+    /// it cancels, unless the `unstable` feature binds it to copy.
+    /// Alt+C copies into the internal register either way.
+    pub fn with_ctrl_c_cancels(self, ctrl_c_cancels: bool) -> Self {
+        let action = if ctrl_c_cancels {
+            Action::Cancel
+        } else {
+            Action::Copy
+        };
+        Self {
+            keymap: self
+                .keymap
+                .bind(KeyCode::Char('c'), KeyModifiers::CONTROL, action),
+            ..self
+        }
+    }
+
+    /// Set whether Enter submits when the editor is read-only,
+    /// rather than doing nothing.
+    pub fn with_read_only_enter_submits(self, read_only_enter_submits: bool) -> Self {
+        Self {
+            read_only_enter_submits,
+            ..self
+        }
+    }
+
+    /// Insert a new line, continuing the list marker of the current line.
+    fn newline(&self, editor: &mut Editor) {
+        let line = editor.curr_ln().into_owned();
+        let indent = line.len() - line.trim_start().len();
+        let rest = &line[indent..];
+        let indent_len = line[..indent].chars().count();
+
+        let matched = self
+            .list_markers
+            .iter()
+            .find_map(|marker| marker.matches(rest));
+        match matched {
+            Some((len, _)) if rest.chars().count() == len => {
+                editor.delete_ln_range(indent_len, indent_len + len);
+            }
+            Some((_, next)) if editor.fits_lines(1) => {
+                editor.type_char('\n');
+                editor.insert_str(&line[..indent]);
+                editor.insert_str(&next);
             }
+            Some(_) => {}
+            None => editor.type_char('\n'),
+        }
+    }
 
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('c') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Some(txt) = editor.curr_sel() {
-                        clipboard.set_text(txt.to_string()).unwrap();
-                    } else {
-                        clipboard.set_text(editor.curr_ln().to_string()).unwrap();
+    /// Handle a key while a prompt is active.
+    fn process_prompt_key(&mut self, editor: &mut Editor, event: KeyEvent) {
+        let prompt = match &mut self.prompt {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        if event.code == KeyCode::Esc {
+            self.prompt = None;
+            return;
+        }
+        match prompt {
+            Prompt::Search { query, origin } => {
+                match event.code {
+                    KeyCode::Enter if event.modifiers.contains(KeyModifiers::SHIFT) => {
+                        editor.find_prev(query);
+                    }
+                    KeyCode::Enter => {
+                        editor.find_next(query);
+                    }
+                    KeyCode::Backspace | KeyCode::Char(_) => {
+                        match event.code {
+                            KeyCode::Char(c) => query.push(c),
+                            _ => {
+                                query.pop();
+                            }
+                        }
+                        editor.selection.anchor = None;
+                        editor.selection.focus = *origin;
+                        editor.find_next(query);
+                    }
+                    _ => {}
+                }
+                if !query.is_empty() && editor.search(query).is_empty() {
+                    editor.set_status(format!("Search: {} (no matches)", query));
+                } else {
+                    editor.set_status(format!("Search: {}", query));
+                }
+            }
+            Prompt::GoToLine { input } => {
+                match event.code {
+                    KeyCode::Enter => {
+                        if let Ok(ln) = input.parse::<usize>() {
+                            editor.move_to_line(ln.saturating_sub(1), false);
+                        }
+                        self.prompt = None;
+                        return;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
                     }
+                    KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+                    _ => {}
                 }
+                editor.set_status(format!("Go to line: {}", input));
             }
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('x') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Some(txt) = editor.curr_sel() {
-                        clipboard.set_text(txt.to_string()).unwrap();
+        }
+    }
+
+    /// Act upon the editor given a key event, without reading from the
+    /// terminal, so synthetic keys can be fed in such as in tests.
+    pub fn process_key_event(&mut self, editor: &mut Editor, event: KeyEvent) -> Result<Signal> {
+        if self.prompt.is_some() {
+            self.process_prompt_key(editor, event);
+            return Ok(Signal::Continue);
+        }
+
+        match self.keymap.lookup(event.code, event.modifiers) {
+            Some((action, anchored)) => self.perform(editor, action, anchored),
+            // Enter with modifiers that are not bound still breaks the line.
+            None if event.code == KeyCode::Enter => self.perform(editor, Action::Newline, false),
+            None => {
+                // Windows reports AltGr as Ctrl+Alt.
+                let modifiers = event.modifiers - KeyModifiers::SHIFT;
+                let altgr = KeyModifiers::CONTROL | KeyModifiers::ALT;
+                if modifiers.is_empty() || modifiers == altgr {
+                    if let KeyCode::Char(c) = event.code {
+                        editor.type_char(c);
+                    }
+                }
+                Ok(Signal::Continue)
+            }
+        }
+    }
+
+    /// Insert pasted text at once, rather than handling it as typed keys,
+    /// so newlines do not submit. Only the first line goes into a prompt.
+    fn paste(&mut self, editor: &mut Editor, text: &str) {
+        if self.prompt.is_some() {
+            for c in text.lines().next().unwrap_or_default().chars() {
+                let event = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+                self.process_prompt_key(editor, event);
+            }
+            return;
+        }
+        // Terminals send carriage returns for the line breaks of a paste.
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        editor.insert_str(&text);
+    }
+
+    /// Perform an action upon the editor.
+    fn perform(&mut self, editor: &mut Editor, action: Action, anchored: bool) -> Result<Signal> {
+        match action {
+            Action::MoveLineDown => editor.move_line_down(),
+            Action::MoveLineUp => editor.move_line_up(),
+            Action::MoveParagraphDown => editor.move_paragraph_down(anchored),
+            Action::MoveParagraphUp => editor.move_paragraph_up(anchored),
+            Action::MoveDown => editor.move_down(anchored),
+            Action::MoveUp => editor.move_up(anchored),
+            Action::MoveLeft => editor.move_left(anchored),
+            Action::MoveRight => editor.move_right(anchored),
+
+            Action::PageDown => editor.page_down(anchored),
+            Action::PageUp => editor.page_up(anchored),
+            Action::MoveToBottom => editor.move_to_bottom(),
+            Action::MoveToTop => editor.move_to_top(),
+            Action::MoveHome => editor.move_home(anchored),
+            Action::MoveLineEnd => editor.move_to_line_end(anchored),
+            Action::MoveToMatchingBracket => editor.move_to_matching_bracket(anchored),
+
+            Action::BackspaceWord => editor.backspace_word(),
+            Action::DeleteWord => editor.delete_word(),
+            Action::Backspace => editor.backspace(),
+            Action::Delete => editor.delete(),
+            Action::DeleteToLineEnd => editor.delete_to_line_end(),
+            Action::TransposeChars => editor.transpose_chars(),
+            Action::Uppercase => editor.uppercase_selection(),
+            Action::Lowercase => editor.lowercase_selection(),
+            Action::ToggleCase => editor.toggle_case_selection(),
+            Action::DeleteToLineStart => editor.delete_to_line_start(),
+
+            Action::Search => {
+                self.prompt = Some(Prompt::Search {
+                    query: String::new(),
+                    origin: editor.selection.focus,
+                });
+                editor.set_status("Search: ");
+            }
+            Action::GoToLine => {
+                self.prompt = Some(Prompt::GoToLine {
+                    input: String::new(),
+                });
+                editor.set_status("Go to line: ");
+            }
+            Action::DuplicateLine => editor.duplicate_line(),
+            Action::JoinLines => editor.join_lines(),
+            Action::ToggleComment => editor.toggle_line_comment(&self.comment_prefix),
+            Action::Yank => editor.yank(),
+            Action::PasteRegister => editor.paste_register(),
+            Action::ToggleBlockSelection => editor.selection.block = !editor.selection.block,
+            Action::Undo => editor.undo(),
+            Action::Redo => editor.redo(),
+
+            Action::ToggleAltscreen => editor.toggle_altscreen(),
+
+            Action::Copy => match &mut self.clipboard {
+                Some(clipboard) => {
+                    let txt = match editor.curr_sel() {
+                        Some(txt) => txt.to_string(),
+                        None => editor.curr_ln().to_string(),
+                    };
+                    clipboard.set(txt.clone());
+                    editor.set_copied(txt);
+                }
+                None => editor.yank(),
+            },
+            Action::Cut if editor.read_only() => {}
+            Action::Cut => {
+                let txt = match editor.curr_sel() {
+                    Some(txt) => {
+                        let txt = txt.to_string();
                         editor.delete();
-                    } else {
-                        clipboard
-                            .set_text(editor.remove_line(editor.selection.focus.ln))
-                            .unwrap();
+                        txt
                     }
+                    None => editor.remove_line(editor.selection.focus.ln),
+                };
+                match &mut self.clipboard {
+                    Some(clipboard) => clipboard.set(txt.clone()),
+                    None => editor.set_register(txt.clone()),
                 }
+                editor.set_copied(txt);
             }
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('v') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Ok(txt) = clipboard.get_text() {
+            Action::Paste => match &mut self.clipboard {
+                Some(clipboard) => {
+                    if let Some(txt) = clipboard.get() {
                         editor.insert_str(&txt);
                     }
                 }
-            }
+                None => editor.paste_register(),
+            },
 
-            KeyCode::Tab => {
+            Action::Indent | Action::Dedent if editor.read_only() => {}
+            Action::Indent if editor.selects_lines() => {
+                let indent = if self.hard_tabs {
+                    "\t".to_string()
+                } else {
+                    " ".repeat(editor.tab_width())
+                };
+                editor.indent_selection(&indent);
+            }
+            Action::Indent if self.hard_tabs => editor.type_char('\t'),
+            Action::Indent => {
                 editor.clamp();
-                let soft = 4 - editor.selection.focus.col % 4;
-                for _ in 0..soft {
-                    editor.insert_char(0, ' ');
+                let tab_width = editor.tab_width();
+                let soft = tab_width - editor.selection.focus.col % tab_width;
+                if editor.fits_chars(soft) {
+                    for _ in 0..soft {
+                        editor.insert_char(0, ' ');
+                    }
+                    editor.selection.focus.col += soft;
                 }
-                editor.selection.focus.col += soft;
             }
-            KeyCode::BackTab => {
+            Action::Dedent => {
                 editor.clamp();
+                editor.dedent_selection();
+            }
+            Action::Submit => return Ok(Signal::Submit),
+            Action::Cancel => return Ok(Signal::Cancel),
+            Action::Newline | Action::NewlineOrSubmit if editor.read_only() => {
+                if self.read_only_enter_submits {
+                    return Ok(Signal::Submit);
+                }
+            }
+            Action::NewlineOrSubmit if editor.at_buffer_end() && editor.at_line_start() => {
+                return Ok(Signal::Submit);
+            }
+            Action::Newline | Action::NewlineOrSubmit => self.newline(editor),
+        }
+        Ok(Signal::Continue)
+    }
+}
+
+/// Keybinding wrapper that accepts a numeric count prefix,
+/// such as `5` followed by `Down`, and repeats the next
+/// command of the inner keybinding that many times.
+///
+/// Digits are only taken as a count while the inner keybinding accepts
+/// one, such as in the normal mode of `VimKeybinding`, so they can still
+/// be typed as text. Set modifiers with `with_modifiers` to enter counts
+/// with a keybinding that always types digits, such as `NormalKeybinding`.
+///
+/// A leading `0` moves the cursor to the start of the line.
+pub struct CountedKeybinding<K> {
+    inner: K,
+    count: Option<usize>,
+    modifiers: KeyModifiers,
+}
+
+/// Largest count, so that a long run of digits cannot
+/// repeat a command practically forever.
+const MAX_COUNT: usize = 10_000;
+
+impl<K> CountedKeybinding<K> {
+    /// Wrap a keybinding with count prefix support.
+    pub fn new(inner: K) -> Self {
+        Self {
+            inner,
+            count: None,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Set the modifiers held while pressing digits to enter a count,
+    /// such as `Alt`, which then enter a count regardless of the
+    /// inner keybinding.
+    pub fn with_modifiers(self, modifiers: KeyModifiers) -> Self {
+        Self { modifiers, ..self }
+    }
 
-                let leading_spaces = editor
-                    .curr_ln_chars()
-                    .take(4)
-                    .take_while(|c| c.is_whitespace())
-                    .count();
+    /// Get the count accumulated so far, if any.
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
+}
 
-                editor.delete_ln_range(0, leading_spaces);
+impl<K: Keybinding> Keybinding for CountedKeybinding<K> {
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal> {
+        if let Event::Key(KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        {
+            let counting = modifiers == self.modifiers
+                && (!modifiers.is_empty() || self.inner.accepts_count());
+            if let Some(digit) = c.to_digit(10).filter(|_| counting) {
+                match self.count {
+                    None if digit == 0 => editor.move_to_col(0, false),
+                    count => {
+                        let count = count.unwrap_or(0) * 10 + digit as usize;
+                        self.count = Some(count.min(MAX_COUNT));
+                    }
+                }
+                return Ok(Signal::Continue);
             }
-            KeyCode::Esc => return Ok(false),
-            KeyCode::Enter => {
-                if !alt && editor.curr_ln_len() == 0 && editor.selection.focus.ln + 1 == ln_count {
-                    return Ok(false);
+        }
+
+        // Releases, repeats and non-key events such as mouse moves
+        // neither use up nor repeat the pending count.
+        if !matches!(
+            event,
+            Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            })
+        ) {
+            return self.inner.process_event(editor, event);
+        }
+
+        let count = self.count.take().unwrap_or(1);
+        for _ in 0..count {
+            let signal = self.inner.process_event(editor, event.clone())?;
+            if signal != Signal::Continue {
+                return Ok(signal);
+            }
+        }
+        Ok(Signal::Continue)
+    }
+}
+
+/// Modal keybinding resembling vim, with normal and insert modes.
+///
+/// Normal mode supports `h` `j` `k` `l`, `i` `a` `o`, `x`, `dd`, `0`, `$`,
+/// `gg`, `G` and `:` to submit. Insert mode behaves like the inner
+/// keybinding, and `Esc` returns to normal mode.
+pub struct VimKeybinding<K = NormalKeybinding> {
+    insert: K,
+    mode: VimMode,
+    pending: Option<char>,
+}
+
+/// Mode of the vim keybinding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+}
+
+impl Default for VimKeybinding {
+    fn default() -> Self {
+        Self::new(NormalKeybinding::default())
+    }
+}
+
+impl<K> VimKeybinding<K> {
+    /// Create a vim keybinding starting in normal mode,
+    /// using a keybinding for insert mode.
+    pub fn new(insert: K) -> Self {
+        Self {
+            insert,
+            mode: VimMode::Normal,
+            pending: None,
+        }
+    }
+
+    /// Get the current mode.
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Handle a key in normal mode.
+    fn process_normal_key(&mut self, editor: &mut Editor, c: char) -> Signal {
+        match (self.pending.take(), c) {
+            (Some('d'), 'd') => editor.delete_line(),
+            (Some('g'), 'g') => {
+                editor.move_to_top();
+                editor.move_to_col(0, false);
+            }
+            (_, 'd') | (_, 'g') => self.pending = Some(c),
+            (_, 'h') => editor.move_left(false),
+            (_, 'j') => editor.move_down(false),
+            (_, 'k') => editor.move_up(false),
+            (_, 'l') => editor.move_right(false),
+            (_, '0') => editor.move_to_col(0, false),
+            (_, '$') => editor.move_to_line_end(false),
+            (_, 'G') => editor.move_to_bottom(),
+            (_, 'x') => {
+                editor.clamp();
+                if !editor.at_line_end() {
+                    editor.delete();
+                }
+            }
+            (_, 'i') => self.mode = VimMode::Insert,
+            (_, 'a') => {
+                editor.clamp();
+                if !editor.at_line_end() {
+                    editor.move_right(false);
+                }
+                self.mode = VimMode::Insert;
+            }
+            (_, 'o') => {
+                editor.move_to_line_end(false);
+                editor.type_char('\n');
+                self.mode = VimMode::Insert;
+            }
+            (_, ':') => return Signal::Submit,
+            _ => {}
+        }
+        Signal::Continue
+    }
+}
+
+impl<K: Keybinding> Keybinding for VimKeybinding<K> {
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal> {
+        let mode = self.mode;
+        let signal = match (self.mode, &event) {
+            (_, Event::Key(k)) if k.kind == KeyEventKind::Release => Signal::Continue,
+            (VimMode::Insert, Event::Key(k)) if k.code == KeyCode::Esc => {
+                self.mode = VimMode::Normal;
+                editor.clamp();
+                if editor.selection.focus.col > 0 {
+                    editor.move_left(false);
+                }
+                Signal::Continue
+            }
+            (VimMode::Insert, _) => self.insert.process_event(editor, event)?,
+            (VimMode::Normal, Event::Key(k)) => {
+                if let KeyCode::Char(c) = k.code {
+                    self.process_normal_key(editor, c)
                 } else {
-                    editor.type_char('\n');
+                    match k.code {
+                        KeyCode::Esc => self.pending = None,
+                        KeyCode::Left => editor.move_left(false),
+                        KeyCode::Right => editor.move_right(false),
+                        KeyCode::Up => editor.move_up(false),
+                        KeyCode::Down => editor.move_down(false),
+                        _ => {}
+                    }
+                    Signal::Continue
                 }
             }
-            KeyCode::Char(c) => editor.type_char(c),
-            _ => { /* ignored */ }
+            (VimMode::Normal, _) => Signal::Continue,
+        };
+        // Show the mode when entering it, and again after the status is
+        // cleared by a key, without hiding a status set in insert mode.
+        if self.mode == VimMode::Insert && (mode != VimMode::Insert || editor.status().is_none()) {
+            editor.set_status("-- INSERT --");
         }
-        Ok(true)
+        Ok(signal)
+    }
+
+    fn accepts_count(&self) -> bool {
+        self.mode == VimMode::Normal
     }
 }
 
@@ -129,22 +761,619 @@ impl NormalKeybinding {
 pub struct DebugKeybinding;
 
 impl Keybinding for DebugKeybinding {
-    fn read(&self, editor: &mut Editor) -> Result<bool> {
-        let key_event = read()?;
-        match key_event {
-            Event::Key(k) => Self::process_key_event(editor, k),
-            _ => Ok(true),
+    fn process_event(&mut self, editor: &mut Editor, event: Event) -> Result<Signal> {
+        match event {
+            Event::Key(k) if k.kind != KeyEventKind::Release => Self::process_key_event(editor, k),
+            _ => Ok(Signal::Continue),
         }
     }
 }
 
 impl DebugKeybinding {
-    fn process_key_event(editor: &mut Editor, event: KeyEvent) -> Result<bool> {
+    fn process_key_event(editor: &mut Editor, event: KeyEvent) -> Result<Signal> {
         let code = event.code;
         match code {
-            KeyCode::Esc => return Ok(false),
+            KeyCode::Esc => return Ok(Signal::Submit),
             _ => editor.insert_str(&format!("{:#?}", event)),
         }
-        Ok(true)
+        Ok(Signal::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::MouseEvent;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn key_with(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn chars(text: &str) -> Vec<Event> {
+        text.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
+
+    #[test]
+    fn count_repeats_motion() {
+        let mut editor = Editor::from("0\n1\n2\n3\n4\n5\n6");
+        let mut keybinding = CountedKeybinding::new(VimKeybinding::default());
+        editor
+            .replay(
+                &mut keybinding,
+                vec![key(KeyCode::Char('5')), key(KeyCode::Down)],
+            )
+            .unwrap();
+        assert_eq!(editor.selection.focus.ln, 5);
+        assert_eq!(keybinding.count(), None);
+    }
+
+    #[test]
+    fn count_survives_releases_and_mouse_moves() {
+        let mut editor = Editor::from("0\n1\n2\n3\n4\n5\n6");
+        let mut keybinding = CountedKeybinding::new(VimKeybinding::default());
+        let release = KeyEvent::new_with_kind(
+            KeyCode::Char('5'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+        let moved = MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        };
+        let events = vec![
+            key(KeyCode::Char('5')),
+            Event::Key(release),
+            Event::Mouse(moved),
+            key(KeyCode::Down),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.selection.focus.ln, 5);
+        assert_eq!(keybinding.count(), None);
+    }
+
+    #[test]
+    fn count_repeats_backspace_with_modifiers() {
+        let mut editor = Editor::from("abcdef");
+        editor.move_to_line_end(false);
+        let mut keybinding =
+            CountedKeybinding::new(NormalKeybinding::default()).with_modifiers(KeyModifiers::ALT);
+        let events = vec![
+            key_with(KeyCode::Char('3'), KeyModifiers::ALT),
+            key(KeyCode::Backspace),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "abc");
+    }
+
+    #[test]
+    fn digits_are_typed_when_not_counting() {
+        let mut editor = Editor::default();
+        let mut keybinding = CountedKeybinding::new(NormalKeybinding::default());
+        editor.replay(&mut keybinding, chars("a12b")).unwrap();
+        assert_eq!(editor.contents(), "a12b");
+
+        // Insert mode of the vim keybinding types digits too.
+        let mut editor = Editor::default();
+        let mut keybinding = CountedKeybinding::new(VimKeybinding::default());
+        editor.replay(&mut keybinding, chars("ia12b")).unwrap();
+        assert_eq!(editor.contents(), "a12b");
+    }
+
+    #[test]
+    fn count_is_capped() {
+        let mut editor = Editor::default();
+        let mut keybinding = CountedKeybinding::new(VimKeybinding::default());
+        editor
+            .replay(&mut keybinding, chars("99999999999999999999999"))
+            .unwrap();
+        assert_eq!(keybinding.count(), Some(MAX_COUNT));
+    }
+
+    #[test]
+    fn leading_zero_moves_to_line_start() {
+        let mut editor = Editor::from("abc");
+        editor.move_to_line_end(false);
+        let mut keybinding = CountedKeybinding::new(VimKeybinding::default());
+        editor.replay(&mut keybinding, chars("0")).unwrap();
+        assert_eq!(editor.selection.focus.col, 0);
+        assert_eq!(keybinding.count(), None);
+    }
+
+    #[test]
+    fn backtab_removes_a_tab() {
+        let mut editor = Editor::from("\t\tab");
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default();
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::BackTab)])
+            .unwrap();
+        assert_eq!(editor.contents(), "\tab");
+        assert_eq!(editor.selection.focus.col, 3);
+    }
+
+    #[test]
+    fn backtab_removes_up_to_a_tab_width_of_spaces() {
+        let mut editor = Editor::from("      ab\n  cd").with_tab_width(4);
+        let mut keybinding = NormalKeybinding::default();
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::BackTab)])
+            .unwrap();
+        assert_eq!(editor.contents(), "  ab\n  cd");
+        assert_eq!(editor.selection.focus.col, 0);
+
+        // Terminals report BackTab with Shift held.
+        let events = vec![
+            key(KeyCode::Down),
+            key_with(KeyCode::BackTab, KeyModifiers::SHIFT),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "  ab\ncd");
+    }
+
+    fn newline_in_list(contents: &str) -> Editor {
+        let mut editor = Editor::from(contents);
+        editor.move_to_bottom();
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default().with_list_markers(ListMarker::defaults());
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::Enter)])
+            .unwrap();
+        editor
+    }
+
+    #[test]
+    fn newline_continues_bullets() {
+        let editor = newline_in_list("  - item");
+        assert_eq!(editor.contents(), "  - item\n  - ");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 4 });
+    }
+
+    #[test]
+    fn newline_increments_numbers() {
+        let editor = newline_in_list("9. item");
+        assert_eq!(editor.contents(), "9. item\n10. ");
+    }
+
+    #[test]
+    fn newline_removes_an_empty_marker() {
+        let editor = newline_in_list("- item\n  * ");
+        assert_eq!(editor.contents(), "- item\n  ");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 2 });
+    }
+
+    #[test]
+    fn newline_does_not_continue_an_overflowing_number() {
+        let line = format!("{}. item", usize::MAX);
+        let editor = newline_in_list(&line);
+        assert_eq!(editor.line_count(), 2);
+        assert_eq!(editor.line(1), "");
+    }
+
+    #[test]
+    fn tab_inserts_spaces_to_the_next_stop() {
+        let mut editor = Editor::default().with_tab_width(2);
+        let mut keybinding = NormalKeybinding::default();
+        let events = vec![
+            key(KeyCode::Tab),
+            key(KeyCode::Char('b')),
+            key(KeyCode::Tab),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "  b ");
+        assert_eq!(editor.selection.focus.col, 4);
+
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::BackTab)])
+            .unwrap();
+        assert_eq!(editor.contents(), "b ");
+    }
+
+    #[test]
+    fn modified_enter_inserts_a_newline() {
+        let mut editor = Editor::from("ab");
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default().with_blank_line_submit(true);
+        let events = vec![
+            key_with(KeyCode::Enter, KeyModifiers::SHIFT),
+            key_with(KeyCode::Enter, KeyModifiers::SHIFT),
+        ];
+        let signal = editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(signal, Signal::Continue);
+        assert_eq!(editor.line_count(), 3);
+    }
+
+    #[test]
+    fn control_letters_are_not_typed() {
+        let mut editor = Editor::default();
+        let mut keybinding = NormalKeybinding::default();
+        let control_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        let events = vec![
+            key_with(KeyCode::Char('Q'), control_shift),
+            key_with(KeyCode::Char('q'), KeyModifiers::ALT),
+            key_with(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            key_with(
+                KeyCode::Char('@'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "A@");
+    }
+
+    #[test]
+    fn vim_dd_deletes_one_line() {
+        let mut editor = Editor::from("a\n\nb\nc");
+        editor.selection.focus = Cursor { ln: 1, col: 0 };
+        let mut keybinding = VimKeybinding::default();
+        editor.replay(&mut keybinding, chars("dd")).unwrap();
+        assert_eq!(editor.contents(), "a\nb\nc");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        // The last line takes the line break before it.
+        editor.replay(&mut keybinding, chars("jdd")).unwrap();
+        assert_eq!(editor.contents(), "a\nb");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        editor.replay(&mut keybinding, chars("dddd")).unwrap();
+        assert_eq!(editor.contents(), "");
+    }
+
+    #[test]
+    fn vim_esc_stays_on_the_line() {
+        let mut editor = Editor::from("ab\ncd");
+        editor.selection.focus = Cursor { ln: 1, col: 0 };
+        let mut keybinding = VimKeybinding::default();
+        let mut events = chars("i");
+        events.push(key(KeyCode::Esc));
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(keybinding.mode(), VimMode::Normal);
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        let mut events = chars("ax");
+        events.push(key(KeyCode::Esc));
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "ab\ncxd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 1 });
+    }
+
+    #[test]
+    fn vim_insert_status_does_not_hide_other_statuses() {
+        let mut editor = Editor::default();
+        let mut keybinding = VimKeybinding::default();
+        editor.replay(&mut keybinding, chars("i")).unwrap();
+        assert_eq!(editor.status(), Some("-- INSERT --"));
+        editor.replay(&mut keybinding, chars("a")).unwrap();
+        assert_eq!(editor.status(), Some("-- INSERT --"));
+
+        let search = key_with(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        editor.replay(&mut keybinding, vec![search]).unwrap();
+        assert_eq!(editor.status(), Some("Search: "));
+
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::Esc)])
+            .unwrap();
+        assert_eq!(keybinding.mode(), VimMode::Normal);
+        assert_eq!(editor.status(), None);
+    }
+
+    #[test]
+    fn home_toggles_between_indent_and_line_start() {
+        let mut editor = Editor::from("    abc");
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default();
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::Home)])
+            .unwrap();
+        assert_eq!(editor.selection.focus.col, 4);
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::Home)])
+            .unwrap();
+        assert_eq!(editor.selection.focus.col, 0);
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::Home)])
+            .unwrap();
+        assert_eq!(editor.selection.focus.col, 4);
+    }
+
+    #[test]
+    fn shift_home_extends_the_selection() {
+        let mut editor = Editor::from("    abc");
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default();
+        let shift_home = key_with(KeyCode::Home, KeyModifiers::SHIFT);
+        editor
+            .replay(&mut keybinding, vec![shift_home.clone()])
+            .unwrap();
+        assert_eq!(editor.curr_sel().unwrap(), "abc");
+        editor.replay(&mut keybinding, vec![shift_home]).unwrap();
+        assert_eq!(editor.curr_sel().unwrap(), "    abc");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 7 }));
+    }
+
+    #[test]
+    fn tab_indents_a_selection_of_lines() {
+        let mut editor = Editor::from("ab\ncd\nef").with_tab_width(2);
+        let mut keybinding = NormalKeybinding::default();
+        let events = vec![
+            key(KeyCode::Right),
+            key_with(KeyCode::Down, KeyModifiers::SHIFT),
+            key_with(KeyCode::Down, KeyModifiers::SHIFT),
+            key(KeyCode::Tab),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "  ab\n  cd\n  ef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 3 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 3 });
+
+        editor
+            .replay(&mut keybinding, vec![key(KeyCode::BackTab)])
+            .unwrap();
+        assert_eq!(editor.contents(), "ab\ncd\nef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 1 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 1 });
+    }
+
+    #[test]
+    fn typing_into_a_read_only_editor_changes_nothing() {
+        let mut editor = Editor::from("ab\ncd").with_read_only(true);
+        let mut keybinding = NormalKeybinding::default();
+        let mut events = chars("xy");
+        events.extend([
+            key(KeyCode::Backspace),
+            key(KeyCode::Delete),
+            key(KeyCode::Tab),
+            key(KeyCode::Enter),
+            Event::Paste("pasted".to_string()),
+            key(KeyCode::Down),
+            key(KeyCode::End),
+        ]);
+        let signal = editor.replay(&mut keybinding, events).unwrap();
+        assert!(matches!(signal, Signal::Continue));
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 2 });
+    }
+
+    #[test]
+    fn max_lines_stalls_the_line_count() {
+        let mut editor = Editor::default().with_max_lines(Some(3));
+        let mut keybinding = NormalKeybinding::default();
+        let mut events = chars("a");
+        events.extend((0..4).map(|_| key(KeyCode::Enter)));
+        events.extend(chars("b"));
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.line_count(), 3);
+        assert_eq!(editor.line(2), "b");
+
+        editor
+            .replay(&mut keybinding, vec![Event::Paste("c\nd\ne".to_string())])
+            .unwrap();
+        assert_eq!(editor.line_count(), 3);
+        assert_eq!(editor.line(2), "bc");
+    }
+
+    #[test]
+    fn max_chars_truncates_a_paste() {
+        let mut editor = Editor::from("ab").with_max_chars(Some(6));
+        editor.move_to_line_end(false);
+        let mut keybinding = NormalKeybinding::default();
+        editor
+            .replay(&mut keybinding, vec![Event::Paste("cdéfgh".to_string())])
+            .unwrap();
+        assert_eq!(editor.contents(), "abcdéf");
+        assert_eq!(editor.selection.focus.col, 6);
+
+        editor.replay(&mut keybinding, chars("x")).unwrap();
+        assert_eq!(editor.contents(), "abcdéf");
+    }
+
+    #[test]
+    fn ctrl_enter_and_ctrl_d_submit_anywhere() {
+        let mut keybinding = NormalKeybinding::default();
+        for submit in [
+            key_with(KeyCode::Enter, KeyModifiers::CONTROL),
+            key_with(KeyCode::Char('d'), KeyModifiers::CONTROL),
+        ] {
+            let mut editor = Editor::from("ab\ncd");
+            editor.move_right(false);
+            let signal = editor.replay(&mut keybinding, vec![submit]).unwrap();
+            assert_eq!(signal, Signal::Submit);
+            assert_eq!(editor.contents(), "ab\ncd");
+        }
+    }
+
+    #[test]
+    fn enter_on_a_blank_last_line_submits_only_when_enabled() {
+        let mut editor = Editor::from("ab\n");
+        editor.move_to_bottom();
+        let mut keybinding = NormalKeybinding::default();
+        let signal = editor
+            .replay(&mut keybinding, vec![key(KeyCode::Enter)])
+            .unwrap();
+        assert_eq!(signal, Signal::Continue);
+        assert_eq!(editor.line_count(), 3);
+
+        let mut keybinding = NormalKeybinding::default().with_blank_line_submit(true);
+        let signal = editor
+            .replay(&mut keybinding, vec![key(KeyCode::Enter)])
+            .unwrap();
+        assert_eq!(signal, Signal::Submit);
+        assert_eq!(editor.line_count(), 3);
+    }
+
+    fn press(keybinding: &mut NormalKeybinding, editor: &mut Editor, code: KeyCode) -> Signal {
+        keybinding
+            .process_key_event(editor, KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn arrows_move_the_focus() {
+        let mut editor = Editor::from("abc\nd");
+        let mut keybinding = NormalKeybinding::default();
+        press(&mut keybinding, &mut editor, KeyCode::Right);
+        press(&mut keybinding, &mut editor, KeyCode::Right);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+        press(&mut keybinding, &mut editor, KeyCode::Down);
+        // The column is kept for moving back up, but clamped on use.
+        let focus = editor.clamp_cursor(editor.selection.focus);
+        assert_eq!(focus, Cursor { ln: 1, col: 1 });
+        press(&mut keybinding, &mut editor, KeyCode::Up);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+        press(&mut keybinding, &mut editor, KeyCode::Left);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 1 });
+        assert_eq!(editor.selection.anchor, None);
+        assert_eq!(editor.contents(), "abc\nd");
+    }
+
+    #[test]
+    fn backspace_at_line_start_joins_lines() {
+        let mut editor = Editor::from("ab\ncd");
+        editor.move_down(false);
+        let mut keybinding = NormalKeybinding::default();
+        press(&mut keybinding, &mut editor, KeyCode::Backspace);
+        assert_eq!(editor.contents(), "abcd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+    }
+
+    #[test]
+    fn tab_inserts_an_indent_at_the_focus() {
+        let mut editor = Editor::from("ab").with_tab_width(4);
+        editor.move_right(false);
+        let mut keybinding = NormalKeybinding::default();
+        press(&mut keybinding, &mut editor, KeyCode::Tab);
+        assert_eq!(editor.contents(), "a   b");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 4 });
+    }
+
+    #[test]
+    fn enter_splits_the_line() {
+        let mut editor = Editor::from("abcd");
+        editor.move_to_col(2, false);
+        let mut keybinding = NormalKeybinding::default();
+        let signal = press(&mut keybinding, &mut editor, KeyCode::Enter);
+        assert_eq!(signal, Signal::Continue);
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+    }
+
+    #[test]
+    fn esc_submits_by_default() {
+        let mut editor = Editor::from("ab");
+        let mut keybinding = NormalKeybinding::default();
+        let signal = editor
+            .replay(&mut keybinding, vec![key(KeyCode::Esc)])
+            .unwrap();
+        assert_eq!(signal, Signal::Submit);
+        assert_eq!(editor.contents(), "ab");
+    }
+
+    #[test]
+    fn esc_cancels_when_enabled() {
+        let mut editor = Editor::from("ab");
+        let mut keybinding = NormalKeybinding::default().with_esc_cancels(true);
+        let signal = editor
+            .replay(&mut keybinding, vec![key(KeyCode::Esc)])
+            .unwrap();
+        assert_eq!(signal, Signal::Cancel);
+
+        let mut keybinding = keybinding.with_esc_cancels(false);
+        let signal = editor
+            .replay(&mut keybinding, vec![key(KeyCode::Esc)])
+            .unwrap();
+        assert_eq!(signal, Signal::Submit);
+    }
+
+    /// Clipboard that shares its text with the test.
+    struct StubClipboard(std::rc::Rc<std::cell::RefCell<Option<String>>>);
+
+    impl Clipboard for StubClipboard {
+        fn get(&mut self) -> Option<String> {
+            self.0.borrow().clone()
+        }
+
+        fn set(&mut self, text: String) {
+            *self.0.borrow_mut() = Some(text);
+        }
+    }
+
+    #[test]
+    fn copy_cut_and_paste_use_the_clipboard() {
+        let text = std::rc::Rc::default();
+        let keymap = KeyMap::default()
+            .bind(KeyCode::Char('c'), KeyModifiers::CONTROL, Action::Copy)
+            .bind(KeyCode::Char('x'), KeyModifiers::CONTROL, Action::Cut)
+            .bind(KeyCode::Char('v'), KeyModifiers::CONTROL, Action::Paste);
+        let mut keybinding = NormalKeybinding::default()
+            .with_keymap(keymap)
+            .with_clipboard(StubClipboard(std::rc::Rc::clone(&text)));
+        let mut editor = Editor::from("ab\ncd");
+
+        let events = vec![
+            key_with(KeyCode::Right, KeyModifiers::SHIFT),
+            key_with(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(text.borrow().as_deref(), Some("a"));
+        assert_eq!(editor.register(), "");
+
+        *text.borrow_mut() = None;
+        let events = vec![key_with(KeyCode::Char('x'), KeyModifiers::CONTROL)];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "b\ncd");
+        assert_eq!(text.borrow().as_deref(), Some("a"));
+
+        *text.borrow_mut() = Some("xy".to_string());
+        let events = vec![
+            key(KeyCode::Down),
+            key_with(KeyCode::Char('v'), KeyModifiers::CONTROL),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "b\nxycd");
+    }
+
+    #[test]
+    fn ctrl_c_cancels() {
+        let ctrl_c = || key_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        let mut editor = Editor::from("ab");
+
+        #[cfg(not(feature = "unstable"))]
+        {
+            let mut keybinding = NormalKeybinding::default();
+            let signal = editor.replay(&mut keybinding, vec![ctrl_c()]).unwrap();
+            assert_eq!(signal, Signal::Cancel);
+        }
+
+        let mut keybinding = NormalKeybinding::default().with_ctrl_c_cancels(true);
+        let signal = editor.replay(&mut keybinding, vec![ctrl_c()]).unwrap();
+        assert_eq!(signal, Signal::Cancel);
+        assert_eq!(editor.contents(), "ab");
+
+        let mut keybinding = keybinding.without_clipboard().with_ctrl_c_cancels(false);
+        let signal = editor.replay(&mut keybinding, vec![ctrl_c()]).unwrap();
+        assert_eq!(signal, Signal::Continue);
+        assert_eq!(editor.register(), "ab");
+    }
+
+    #[test]
+    fn cut_leaves_the_clipboard_alone_when_read_only() {
+        let text = std::rc::Rc::new(std::cell::RefCell::new(Some("keep".to_string())));
+        let keymap = KeyMap::default().bind(KeyCode::Char('x'), KeyModifiers::CONTROL, Action::Cut);
+        let mut keybinding = NormalKeybinding::default()
+            .with_keymap(keymap)
+            .with_clipboard(StubClipboard(std::rc::Rc::clone(&text)));
+        let mut editor = Editor::from("ab\ncd").with_read_only(true);
+
+        let events = vec![key_with(KeyCode::Char('x'), KeyModifiers::CONTROL)];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(text.borrow().as_deref(), Some("keep"));
+        assert_eq!(editor.copied(), None);
     }
 }
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// An editing command that a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    MoveParagraphUp,
+    MoveParagraphDown,
+    /// Move up by a screenful.
+    PageUp,
+    /// Move down by a screenful.
+    PageDown,
+    /// Move to the first non-whitespace character of the line,
+    /// or to the start of the line if already there.
+    MoveHome,
+    MoveLineEnd,
+    MoveToTop,
+    MoveToBottom,
+    MoveToMatchingBracket,
+    MoveLineUp,
+    MoveLineDown,
+    Backspace,
+    Delete,
+    BackspaceWord,
+    DeleteWord,
+    DeleteToLineEnd,
+    DeleteToLineStart,
+    /// Swap the characters around the cursor.
+    TransposeChars,
+    /// Convert the selection or word to uppercase.
+    Uppercase,
+    /// Convert the selection or word to lowercase.
+    Lowercase,
+    /// Swap the case of the selection or word. Not bound by default.
+    ToggleCase,
+    DuplicateLine,
+    JoinLines,
+    ToggleComment,
+    Yank,
+    PasteRegister,
+    /// Copy the selection or line to the clipboard.
+    Copy,
+    /// Cut the selection or line to the clipboard.
+    Cut,
+    /// Paste from the clipboard.
+    Paste,
+    /// Switch the selection between a range and a block of columns.
+    ToggleBlockSelection,
+    Undo,
+    Redo,
+    Search,
+    GoToLine,
+    ToggleAltscreen,
+    Indent,
+    Dedent,
+    /// Insert a new line, continuing list markers.
+    Newline,
+    /// Submit on the last empty line, otherwise insert a new line.
+    NewlineOrSubmit,
+    Submit,
+    /// Stop reading input, discarding the content.
+    Cancel,
+}
+
+impl Action {
+    /// Check if the action moves the focus, in which case
+    /// holding Shift extends the selection.
+    pub fn is_motion(self) -> bool {
+        matches!(
+            self,
+            Action::MoveLeft
+                | Action::MoveRight
+                | Action::MoveUp
+                | Action::MoveDown
+                | Action::MoveParagraphUp
+                | Action::MoveParagraphDown
+                | Action::PageUp
+                | Action::PageDown
+                | Action::MoveHome
+                | Action::MoveLineEnd
+                | Action::MoveToMatchingBracket
+        )
+    }
+}
+
+/// Mapping of keys to actions.
+///
+/// Characters that are not bound are typed into the editor,
+/// unless Ctrl or Alt is held.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<(KeyCode, KeyModifiers), Action>,
+}
+
+impl KeyMap {
+    /// Create a keymap without any bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Bind a key to an action, replacing any previous binding.
+    pub fn bind(mut self, code: KeyCode, modifiers: KeyModifiers, action: Action) -> Self {
+        self.bindings.insert((code, modifiers), action);
+        self
+    }
+
+    /// Remove the binding of a key.
+    pub fn unbind(mut self, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        self.bindings.remove(&(code, modifiers));
+        self
+    }
+
+    /// Get the action bound to a key.
+    pub fn get(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        self.bindings.get(&(code, modifiers)).copied()
+    }
+
+    /// Get the action bound to a key, and whether it extends the selection.
+    ///
+    /// Motions bound without Shift also match with Shift held,
+    /// extending the selection. Other actions only match exactly.
+    pub fn lookup(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<(Action, bool)> {
+        if let Some(action) = self.get(code, modifiers) {
+            return Some((action, false));
+        }
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            let action = self.get(code, modifiers - KeyModifiers::SHIFT)?;
+            return action.is_motion().then_some((action, true));
+        }
+        None
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        const NONE: KeyModifiers = KeyModifiers::NONE;
+        const CONTROL: KeyModifiers = KeyModifiers::CONTROL;
+        const ALT: KeyModifiers = KeyModifiers::ALT;
+        const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+
+        let keymap = KeyMap::empty()
+            .bind(KeyCode::Down, ALT, Action::MoveLineDown)
+            .bind(KeyCode::Up, ALT, Action::MoveLineUp)
+            .bind(KeyCode::Down, CONTROL, Action::MoveParagraphDown)
+            .bind(KeyCode::Up, CONTROL, Action::MoveParagraphUp)
+            .bind(KeyCode::Down, NONE, Action::MoveDown)
+            .bind(KeyCode::Up, NONE, Action::MoveUp)
+            .bind(KeyCode::Left, NONE, Action::MoveLeft)
+            .bind(KeyCode::Right, NONE, Action::MoveRight)
+            .bind(KeyCode::PageDown, NONE, Action::PageDown)
+            .bind(KeyCode::PageUp, NONE, Action::PageUp)
+            .bind(KeyCode::End, CONTROL, Action::MoveToBottom)
+            .bind(KeyCode::Home, CONTROL, Action::MoveToTop)
+            .bind(KeyCode::Home, NONE, Action::MoveHome)
+            .bind(KeyCode::End, NONE, Action::MoveLineEnd)
+            .bind(KeyCode::Backspace, CONTROL, Action::BackspaceWord)
+            .bind(KeyCode::Delete, CONTROL, Action::DeleteWord)
+            .bind(KeyCode::Backspace, NONE, Action::Backspace)
+            .bind(KeyCode::Char('h'), CONTROL, Action::Backspace)
+            .bind(KeyCode::Delete, NONE, Action::Delete)
+            .bind(KeyCode::Char('f'), CONTROL, Action::Search)
+            .bind(KeyCode::Char('g'), CONTROL, Action::GoToLine)
+            .bind(KeyCode::Down, ALT | SHIFT, Action::DuplicateLine)
+            .bind(KeyCode::Char('j'), CONTROL, Action::JoinLines)
+            .bind(KeyCode::Char('k'), CONTROL, Action::DeleteToLineEnd)
+            .bind(KeyCode::Char('u'), CONTROL, Action::DeleteToLineStart)
+            .bind(KeyCode::Char('t'), CONTROL, Action::TransposeChars)
+            .bind(KeyCode::Char('u'), ALT, Action::Uppercase)
+            .bind(KeyCode::Char('l'), ALT, Action::Lowercase)
+            .bind(KeyCode::Char('c'), ALT, Action::Yank)
+            .bind(KeyCode::Char('v'), ALT, Action::PasteRegister)
+            .bind(KeyCode::Char('/'), CONTROL, Action::ToggleComment)
+            // Terminals report Ctrl+/ as Ctrl+7.
+            .bind(KeyCode::Char('7'), CONTROL, Action::ToggleComment)
+            .bind(KeyCode::Char(']'), CONTROL, Action::MoveToMatchingBracket)
+            // Terminals report Ctrl+] as Ctrl+5.
+            .bind(KeyCode::Char('5'), CONTROL, Action::MoveToMatchingBracket)
+            .bind(KeyCode::Char('b'), ALT, Action::ToggleBlockSelection)
+            .bind(KeyCode::Char('z'), CONTROL, Action::Undo)
+            .bind(KeyCode::Char('y'), CONTROL, Action::Redo)
+            .bind(KeyCode::F(12), NONE, Action::ToggleAltscreen)
+            .bind(KeyCode::Tab, NONE, Action::Indent)
+            .bind(KeyCode::BackTab, NONE, Action::Dedent)
+            // Most terminals report BackTab with Shift held.
+            .bind(KeyCode::BackTab, SHIFT, Action::Dedent)
+            .bind(KeyCode::Esc, NONE, Action::Submit)
+            .bind(KeyCode::Enter, CONTROL, Action::Submit)
+            // Terminals that cannot tell Ctrl+Enter from Enter can use Ctrl+D.
+            .bind(KeyCode::Char('d'), CONTROL, Action::Submit)
+            .bind(KeyCode::Enter, ALT, Action::Newline)
+            .bind(KeyCode::Enter, NONE, Action::Newline)
+            // Raw mode keeps Ctrl+C from interrupting the program.
+            .bind(KeyCode::Char('c'), CONTROL, Action::Cancel);
+
+        #[cfg(feature = "unstable")]
+        let keymap = keymap
+            .bind(KeyCode::Char('c'), CONTROL, Action::Copy)
+            .bind(KeyCode::Char('x'), CONTROL, Action::Cut)
+            .bind(KeyCode::Char('v'), CONTROL, Action::Paste);
+
+        keymap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+    const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+    const CONTROL: KeyModifiers = KeyModifiers::CONTROL;
+
+    #[test]
+    fn shift_extends_motions() {
+        let keymap = KeyMap::default();
+        assert_eq!(
+            keymap.lookup(KeyCode::Left, NONE),
+            Some((Action::MoveLeft, false))
+        );
+        assert_eq!(
+            keymap.lookup(KeyCode::Left, SHIFT),
+            Some((Action::MoveLeft, true))
+        );
+        assert_eq!(
+            keymap.lookup(KeyCode::Up, CONTROL | SHIFT),
+            Some((Action::MoveParagraphUp, true))
+        );
+    }
+
+    #[test]
+    fn shift_does_not_match_other_actions() {
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.lookup(KeyCode::Char('z'), CONTROL | SHIFT), None);
+        assert_eq!(keymap.lookup(KeyCode::Backspace, SHIFT), None);
+        assert_eq!(
+            keymap.lookup(KeyCode::BackTab, SHIFT),
+            Some((Action::Dedent, false))
+        );
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let keymap = KeyMap::empty()
+            .bind(KeyCode::Char('s'), CONTROL, Action::Submit)
+            .bind(KeyCode::Char('s'), CONTROL, Action::Search);
+        assert_eq!(
+            keymap.get(KeyCode::Char('s'), CONTROL),
+            Some(Action::Search)
+        );
+        let keymap = keymap.unbind(KeyCode::Char('s'), CONTROL);
+        assert_eq!(keymap.get(KeyCode::Char('s'), CONTROL), None);
+    }
+}
@@ -1,18 +1,122 @@
+/// A module that provides clipboards for copying and pasting.
+pub mod clipboard;
 /// A module that contains keybindings for the editor.
 pub mod keybindings;
+/// A module that maps keys to editing actions.
+pub mod keymap;
 pub mod selection;
+/// A module that checks the content before it is submitted.
+pub mod validator;
 
-use std::{borrow::Cow, io::Read};
+mod diff;
+mod history;
+mod search;
 
-use self::{keybindings::Keybinding, selection::{Cursor, Selection}};
-use crate::{Result, renderer::{Renderer}, util::trimmed};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    io::Read,
+    iter::once,
+    ops::Range,
+    str::FromStr,
+};
 
-use ropey::Rope;
+use self::{
+    history::{Edit, History},
+    keybindings::{Keybinding, NormalKeybinding, Signal},
+    selection::{Cursor, Selection},
+    validator::Validator,
+};
+use crate::{renderer::Renderer, util::trimmed, Result};
+
+use crossterm::event::{Event, KeyEventKind};
+use ropey::{Rope, RopeSlice};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Callback called after the content of an editor changes.
+type ChangeCallback = Box<dyn FnMut(&Editor)>;
 
 pub struct Editor {
     pub selection: Selection,
     pub(crate) buf: Rope,
     pub altscreen: bool,
+    recording: Option<Vec<Event>>,
+    history: History,
+    keymap: Option<Box<dyn Keybinding>>,
+    status: Option<String>,
+    copied: Option<String>,
+    register: String,
+    tab_width: usize,
+    smart_backspace: bool,
+    trim_on_submit: bool,
+    newline: Newline,
+    trailing_newline: bool,
+    read_only: bool,
+    max_lines: Option<usize>,
+    max_chars: Option<usize>,
+    changed: bool,
+    on_change: Option<ChangeCallback>,
+    validator: Option<Box<dyn Validator>>,
+    error: Option<String>,
+    scroll: Option<usize>,
+    viewport: Cell<(usize, usize)>,
+    scrollbar: Cell<Option<(u16, u16, usize)>>,
+    resized: Option<(u16, u16)>,
+    baseline: Option<Vec<String>>,
+    diff: RefCell<Option<Vec<LineStatus>>>,
+}
+
+/// Line break style of the content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    /// `\n`, as on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows.
+    CrLf,
+}
+
+impl Newline {
+    /// Detect the style of most of the line breaks of a text.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            Newline::CrLf
+        } else {
+            Newline::Lf
+        }
+    }
+
+    /// Get the line break.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How a line differs from the baseline of an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStatus {
+    Unchanged,
+    /// The line is not in the baseline.
+    Added,
+    /// The line replaces a line of the baseline.
+    Modified,
+    /// The line is unchanged, but lines of the baseline before it
+    /// were removed. At the end of the content, the last line is marked.
+    RemovedAbove,
+}
+
+/// Content and selection of an editor, for saving and restoring a session.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EditorState {
+    pub contents: String,
+    pub selection: Selection,
 }
 
 impl Default for Editor {
@@ -21,36 +125,644 @@ impl Default for Editor {
             buf: Rope::new(),
             selection: Selection::default(),
             altscreen: false,
+            recording: None,
+            history: History::default(),
+            keymap: None,
+            status: None,
+            copied: None,
+            register: String::new(),
+            tab_width: 4,
+            smart_backspace: false,
+            trim_on_submit: false,
+            newline: Newline::Lf,
+            trailing_newline: false,
+            read_only: false,
+            max_lines: None,
+            max_chars: None,
+            changed: false,
+            on_change: None,
+            validator: None,
+            error: None,
+            scroll: None,
+            viewport: Cell::new((0, 0)),
+            scrollbar: Cell::new(None),
+            resized: None,
+            baseline: None,
+            diff: RefCell::new(None),
         }
     }
 }
 
+impl From<&str> for Editor {
+    /// Create an editor with the focus at the start of the content.
+    /// Use `move_to_bottom` to place it at the end instead.
+    fn from(contents: &str) -> Self {
+        Self::default().with_contents(contents)
+    }
+}
+
+impl FromStr for Editor {
+    type Err = Infallible;
+
+    fn from_str(contents: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Self::from(contents))
+    }
+}
+
 impl Editor {
+    /// Set the content of the editor, moving the focus to the start.
+    ///
+    /// The line break style of the content is detected and used by
+    /// `contents`, while the editor works with `\n` internally.
+    pub fn with_contents(mut self, contents: &str) -> Self {
+        self.load(contents);
+        self.selection = Selection::default();
+        self
+    }
+
     /// Set the content of the editor.
-    pub fn set_contents(&mut self, reader: impl Read) -> Result<()> {
-        self.buf = Rope::from_reader(reader)?;
+    ///
+    /// The selection is preserved, clamped into the new content.
+    /// The line break style is detected like in `with_contents`.
+    pub fn set_contents(&mut self, mut reader: impl Read) -> Result<()> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        self.load(&contents);
+        self.selection.focus = self.clamp_cursor(self.selection.focus);
+        self.selection.anchor = self.selection.anchor.map(|c| self.clamp_cursor(c));
+        self.selection.fix_anchor();
+        Ok(())
+    }
+
+    /// Replace the buffer, remembering its line break style if it has any.
+    fn load(&mut self, contents: &str) {
+        if contents.contains('\n') {
+            self.newline = Newline::detect(contents);
+        }
+        self.buf = Rope::from_str(&contents.replace("\r\n", "\n"));
+        self.history.clear();
+        self.diff.take();
+    }
+
+    /// Append the content of a reader to the end of the editor,
+    /// keeping the selection, such as to stream logs into a read-only view.
+    ///
+    /// The appended text starts on a new line, and is not recorded in the
+    /// undo history, so that edits before it can still be undone. It is
+    /// not limited by `with_max_lines` or `with_max_chars` either.
+    pub fn append_contents(&mut self, mut reader: impl Read) -> Result<()> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut text = text.replace("\r\n", "\n");
+        let len = self.buf.len_chars();
+        if len > 0 && self.buf.char(len - 1) != '\n' {
+            text.insert(0, '\n');
+        }
+        self.buf.insert(len, &text);
+        self.changed = true;
+        self.diff.take();
+        Ok(())
+    }
+
+    /// Insert the content of a reader at the focus, replacing the selection,
+    /// like a paste.
+    ///
+    /// The text is read in full first, since the undo history keeps
+    /// a copy of it either way.
+    pub fn insert_from_reader(&mut self, mut reader: impl Read) -> Result<()> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        self.insert_str(&text.replace("\r\n", "\n"));
         Ok(())
     }
 
-    /// Get the content of the editor.
+    /// Get the content and selection of the editor,
+    /// with line breaks in its style.
+    pub fn state(&self) -> EditorState {
+        let contents = self.buf.to_string();
+        EditorState {
+            contents: match self.newline {
+                Newline::Lf => contents,
+                Newline::CrLf => contents.replace('\n', "\r\n"),
+            },
+            selection: self.selection,
+        }
+    }
+
+    /// Restore the content and selection of the editor,
+    /// remembering its line break style like `set_contents`.
+    /// The undo history is cleared.
+    pub fn restore(&mut self, state: &EditorState) {
+        self.load(&state.contents);
+        self.selection = state.selection;
+        self.selection.focus = self.clamp_cursor(self.selection.focus);
+        self.selection.anchor = self.selection.anchor.map(|c| self.clamp_cursor(c));
+        self.selection.fix_anchor();
+    }
+
+    /// Get the content of the editor, with line breaks in its style.
+    ///
+    /// The line break after the last line is left out,
+    /// unless `with_trailing_newline` is set.
     pub fn contents(&self) -> String {
-        trimmed(self.buf.slice(..)).to_string()
+        let mut contents = trimmed(self.buf.slice(..)).to_string();
+        if self.trailing_newline && !contents.is_empty() {
+            contents.push('\n');
+        }
+        match self.newline {
+            Newline::Lf => contents,
+            Newline::CrLf => contents.replace('\n', "\r\n"),
+        }
     }
 
     /// Activate the editor and renderer, and read the input.
-    pub fn read(&mut self, keybinding: impl Keybinding, mut renderer: impl Renderer) -> Result<()> {
-        loop {
+    /// Returns the content if it was submitted, or `None` if cancelled.
+    ///
+    /// The keybinding is held by value and driven mutably, so it may keep
+    /// state between events. Pass `&mut keybinding` to keep the state
+    /// after reading.
+    pub fn read(
+        &mut self,
+        mut keybinding: impl Keybinding,
+        renderer: impl Renderer,
+    ) -> Result<Option<String>> {
+        self.read_with(&mut keybinding, renderer)
+    }
+
+    /// Set the keybinding owned by the editor.
+    pub fn with_keymap(self, keybinding: impl Keybinding + 'static) -> Self {
+        Self {
+            keymap: Some(Box::new(keybinding)),
+            ..self
+        }
+    }
+
+    /// Set the width of an indentation level, 4 by default.
+    pub fn with_tab_width(self, tab_width: usize) -> Self {
+        Self {
+            tab_width: tab_width.max(1),
+            ..self
+        }
+    }
+
+    /// Get the width of an indentation level.
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// Set whether backspace in the indentation of a line removes
+    /// the spaces back to the previous indentation level.
+    pub fn with_smart_backspace(self, smart_backspace: bool) -> Self {
+        Self {
+            smart_backspace,
+            ..self
+        }
+    }
+
+    /// Set whether trailing whitespace is trimmed from
+    /// every line on submit, before the content is validated.
+    pub fn with_trim_on_submit(self, trim_on_submit: bool) -> Self {
+        Self {
+            trim_on_submit,
+            ..self
+        }
+    }
+
+    /// Set the line break style of the content returned by `contents`.
+    pub fn with_newline(self, newline: Newline) -> Self {
+        Self { newline, ..self }
+    }
+
+    /// Set whether `contents` ends non-empty content with a line break,
+    /// as expected at the end of text files.
+    pub fn with_trailing_newline(self, trailing_newline: bool) -> Self {
+        Self {
+            trailing_newline,
+            ..self
+        }
+    }
+
+    /// Get the line break style of the content.
+    pub fn newline(&self) -> Newline {
+        self.newline
+    }
+
+    /// Switch between editing inline and on the full screen.
+    pub fn toggle_altscreen(&mut self) {
+        self.altscreen = !self.altscreen;
+    }
+
+    /// Set whether the content is read-only. Editing does nothing,
+    /// while moving and selecting still work, such as for a pager.
+    pub fn with_read_only(self, read_only: bool) -> Self {
+        Self { read_only, ..self }
+    }
+
+    /// Set whether the content is read-only.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Check if the content is read-only.
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Set the maximum number of lines, past which new lines are
+    /// not inserted. Unlike the height of the renderer, this limits
+    /// the content rather than the view.
+    pub fn with_max_lines(self, max_lines: Option<usize>) -> Self {
+        Self { max_lines, ..self }
+    }
+
+    /// Get the maximum number of lines.
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// Check if a number of lines can be added without
+    /// exceeding the maximum number of lines.
+    pub fn fits_lines(&self, lines: usize) -> bool {
+        match self.max_lines {
+            Some(max) => self.line_count() + lines <= max,
+            None => true,
+        }
+    }
+
+    /// Cut text off before the line break that would
+    /// exceed the maximum number of lines.
+    fn fit_lines<'a>(&self, text: &'a str) -> &'a str {
+        let room = match self.max_lines {
+            Some(max) => max.saturating_sub(self.line_count()),
+            None => return text,
+        };
+        match text.match_indices('\n').nth(room) {
+            Some((idx, _)) => &text[..idx],
+            None => text,
+        }
+    }
+
+    /// Set the maximum number of characters, past which text is not
+    /// inserted. Pasted text is cut off to fit.
+    pub fn with_max_chars(self, max_chars: Option<usize>) -> Self {
+        Self { max_chars, ..self }
+    }
+
+    /// Get the maximum number of characters.
+    pub fn max_chars(&self) -> Option<usize> {
+        self.max_chars
+    }
+
+    /// Check if a number of characters can be added without
+    /// exceeding the maximum number of characters.
+    pub fn fits_chars(&self, chars: usize) -> bool {
+        match self.max_chars {
+            Some(max) => self.char_count() + chars <= max,
+            None => true,
+        }
+    }
+
+    /// Cut text off at the character that would
+    /// exceed the maximum number of characters.
+    fn fit_chars<'a>(&self, text: &'a str) -> &'a str {
+        let room = match self.max_chars {
+            Some(max) => max.saturating_sub(self.char_count()),
+            None => return text,
+        };
+        match text.char_indices().nth(room) {
+            Some((idx, _)) => &text[..idx],
+            None => text,
+        }
+    }
+
+    /// Set a callback that is called after each event that changes the
+    /// content, once the focus has been updated, but not after moving.
+    ///
+    /// The callback only reads the editor. It is not called
+    /// again while it runs.
+    pub fn with_on_change(self, on_change: impl FnMut(&Editor) + 'static) -> Self {
+        Self {
+            on_change: Some(Box::new(on_change)),
+            ..self
+        }
+    }
+
+    /// Set the validator that checks the content before it is submitted.
+    /// Invalid content is not submitted, and the error is kept until
+    /// the content changes.
+    pub fn with_validator(self, validator: impl Validator + 'static) -> Self {
+        Self {
+            validator: Some(Box::new(validator)),
+            ..self
+        }
+    }
+
+    /// Get the error of the last submit that failed validation.
+    pub fn validation_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Check the content with the validator, keeping the error if invalid.
+    fn validate(&mut self) -> bool {
+        let result = match &self.validator {
+            Some(validator) => validator.validate(&self.contents()),
+            None => Ok(()),
+        };
+        self.error = result.err();
+        self.error.is_none()
+    }
+
+    /// Trim the content if enabled, then check it with the validator,
+    /// so that the validated content is the content that is returned.
+    fn submit(&mut self) -> bool {
+        if self.trim_on_submit {
+            self.trim_trailing_whitespace();
+            self.commit_history();
+        }
+        self.validate()
+    }
+
+    /// Activate the editor and renderer, and read the input using
+    /// the keybinding owned by the editor. Keeps the keybinding state
+    /// between sessions. Uses the normal keybinding if none was set.
+    pub fn run(&mut self, renderer: impl Renderer) -> Result<Option<String>> {
+        let mut keymap = self
+            .keymap
+            .take()
+            .unwrap_or_else(|| Box::new(NormalKeybinding::default()));
+        let result = self.read_with(keymap.as_mut(), renderer);
+        self.keymap = Some(keymap);
+        result
+    }
+
+    fn read_with(
+        &mut self,
+        keybinding: &mut dyn Keybinding,
+        renderer: impl Renderer,
+    ) -> Result<Option<String>> {
+        let signal = self.session(renderer, |editor| Some(keybinding.read(editor)))?;
+        match signal {
+            Signal::Cancel => Ok(None),
+            _ => Ok(Some(self.contents())),
+        }
+    }
+
+    /// Activate the renderer and feed a sequence of events to the editor
+    /// instead of reading from the terminal, such as to automate or test
+    /// the editor, or to replay a recorded session.
+    ///
+    /// Returns the signal that ended the input, or `Signal::Continue`
+    /// if the events ran out first.
+    ///
+    /// Forward `Event::Resize` events too, so that the renderer
+    /// redraws the frame for the new size of the terminal.
+    pub fn drive(
+        &mut self,
+        mut keybinding: impl Keybinding,
+        renderer: impl Renderer,
+        events: impl IntoIterator<Item = Event>,
+    ) -> Result<Signal> {
+        let mut events = events.into_iter();
+        self.session(renderer, |editor| {
+            let event = events.next()?;
+            editor.begin_event(&event);
+            Some(keybinding.process_event(editor, event))
+        })
+    }
+
+    /// Draw and process events until the input ends or
+    /// the events run out, then finish the renderer.
+    fn session(
+        &mut self,
+        mut renderer: impl Renderer,
+        mut next: impl FnMut(&mut Self) -> Option<Result<Signal>>,
+    ) -> Result<Signal> {
+        let signal = loop {
             renderer.draw(self)?;
             renderer.flush()?;
 
-            if !keybinding.read(self)? {
-                break;
+            let signal = match next(self) {
+                Some(signal) => signal?,
+                None => break Signal::Continue,
+            };
+            self.end_event();
+            if let Some((cols, rows)) = self.resized.take() {
+                renderer.resize(cols, rows)?;
             }
-        }
+            if signal == Signal::Submit && !self.submit() {
+                continue;
+            }
+            if signal != Signal::Continue {
+                break signal;
+            }
+        };
 
         renderer.finish()?;
+        Ok(signal)
+    }
 
-        Ok(())
+    /// Start recording the events processed by the editor.
+    /// Any previous recording is discarded.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stop recording and take the recorded events, if any.
+    pub fn stop_recording(&mut self) -> Option<Vec<Event>> {
+        self.recording.take()
+    }
+
+    /// Get the events recorded so far, if recording.
+    pub fn recording(&self) -> Option<&[Event]> {
+        self.recording.as_deref()
+    }
+
+    /// Prepare the editor for processing an event.
+    pub(crate) fn begin_event(&mut self, event: &Event) {
+        if let Some(recording) = &mut self.recording {
+            recording.push(event.clone());
+        }
+        if is_input(event) {
+            self.status = None;
+            self.scroll = None;
+        }
+        self.copied = None;
+        if let Event::Resize(cols, rows) = *event {
+            self.resized = Some((cols, rows));
+        }
+    }
+
+    /// Scroll the view by a number of lines without moving the focus.
+    ///
+    /// The scroll lasts until the next key press or paste, after which
+    /// the view snaps back to keep the focus visible if it is out of view.
+    pub fn scroll_by(&mut self, lines: isize) {
+        let top = self.scroll.unwrap_or_else(|| self.viewport.get().0);
+        let top = if lines < 0 {
+            top.saturating_sub(lines.unsigned_abs())
+        } else {
+            top.saturating_add(lines as usize)
+        };
+        self.scroll = Some(top.min(self.last_line()));
+    }
+
+    /// Get the line the view was scrolled to, if any.
+    pub fn scroll_top(&self) -> Option<usize> {
+        self.scroll
+    }
+
+    /// Get the range of lines shown in the last frame drawn.
+    pub fn viewport(&self) -> Range<usize> {
+        let (low, high) = self.viewport.get();
+        low..high
+    }
+
+    pub(crate) fn set_viewport(&self, low: usize, high: usize) {
+        self.viewport.set((low, high));
+    }
+
+    /// Check if a terminal cell is on the scrollbar of the last frame drawn.
+    pub fn on_scrollbar(&self, column: u16, row: u16) -> bool {
+        match self.scrollbar.get() {
+            Some((col, top, rows)) => column == col && row >= top && usize::from(row - top) < rows,
+            None => false,
+        }
+    }
+
+    /// Scroll the view so that the scrollbar thumb of the last frame
+    /// drawn starts at a terminal row, clamped to the scrollbar.
+    pub fn scroll_to_scrollbar_row(&mut self, row: u16) {
+        if let Some((_, top, rows)) = self.scrollbar.get() {
+            let row = usize::from(row.saturating_sub(top)).min(rows.saturating_sub(1));
+            let line = row * self.line_count() / rows.max(1);
+            self.scroll = Some(line.min(self.last_line()));
+        }
+    }
+
+    pub(crate) fn set_scrollbar(&self, track: Option<(u16, u16, usize)>) {
+        self.scrollbar.set(track);
+    }
+
+    /// Finish processing an event, committing the history and
+    /// notifying the change callback if the buffer was changed.
+    pub(crate) fn end_event(&mut self) {
+        self.commit_history();
+        if std::mem::take(&mut self.changed) {
+            self.error = None;
+            if let Some(mut on_change) = self.on_change.take() {
+                on_change(self);
+                self.on_change = Some(on_change);
+            }
+        }
+    }
+
+    /// Set a transient status message, which is cleared
+    /// by the next key press or paste.
+    pub fn set_status(&mut self, msg: impl Into<String>) {
+        self.status = Some(msg.into());
+    }
+
+    /// Get the current status message.
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Mark text as copied by the current event, which the renderer
+    /// may put on the terminal clipboard. Cleared on the next event.
+    pub fn set_copied(&mut self, text: impl Into<String>) {
+        self.copied = Some(text.into());
+    }
+
+    /// Get the text copied by the current event.
+    pub fn copied(&self) -> Option<&str> {
+        self.copied.as_deref()
+    }
+
+    /// Re-apply a sequence of events to the editor without rendering.
+    /// Stops early if the keybinding signals the end of input,
+    /// returning the last signal.
+    pub fn replay(
+        &mut self,
+        mut keybinding: impl Keybinding,
+        events: impl IntoIterator<Item = Event>,
+    ) -> Result<Signal> {
+        for event in events {
+            self.begin_event(&event);
+            let signal = keybinding.process_event(self, event)?;
+            self.end_event();
+            if signal == Signal::Submit && !self.submit() {
+                continue;
+            }
+            if signal != Signal::Continue {
+                return Ok(signal);
+            }
+        }
+        Ok(Signal::Continue)
+    }
+
+    /// Undo the last change.
+    pub fn undo(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.commit_history();
+        if let Some(selection) = self.history.undo(&mut self.buf) {
+            self.selection = selection;
+            self.changed = true;
+            self.diff.take();
+        }
+    }
+
+    /// Redo the last undone change.
+    pub fn redo(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.commit_history();
+        if let Some(selection) = self.history.redo(&mut self.buf) {
+            self.selection = selection;
+            self.changed = true;
+            self.diff.take();
+        }
+    }
+
+    /// Limit the undo history by the number of steps and the bytes of
+    /// text retained, whichever is hit first. The oldest steps are
+    /// forgotten first, but the newest step is always kept.
+    /// `None` means no limit, which is the default.
+    pub fn set_undo_limits(&mut self, max_steps: Option<usize>, max_bytes: Option<usize>) {
+        self.history.set_limits(max_steps, max_bytes);
+    }
+
+    /// Close the pending changes into a single undo step.
+    pub fn commit_history(&mut self) {
+        self.history.commit(self.selection);
+    }
+
+    /// Insert text into the buffer, recording it in the history.
+    fn rope_insert(&mut self, idx: usize, text: &str) {
+        self.buf.insert(idx, text);
+        self.changed = true;
+        self.diff.take();
+        let edit = Edit {
+            idx,
+            removed: String::new(),
+            inserted: text.to_string(),
+        };
+        self.history.record(edit, self.selection);
+    }
+
+    /// Remove text from the buffer, recording it in the history.
+    fn rope_remove(&mut self, range: Range<usize>) {
+        let edit = Edit {
+            idx: range.start,
+            removed: self.buf.slice(range.clone()).to_string(),
+            inserted: String::new(),
+        };
+        self.buf.remove(range);
+        self.changed = true;
+        self.diff.take();
+        self.history.record(edit, self.selection);
     }
 
     /// Get the number of lines.
@@ -58,32 +770,122 @@ impl Editor {
         self.buf.len_lines()
     }
 
-    /// Get the number of characters.
+    /// Get the index of the last line.
+    pub fn last_line(&self) -> usize {
+        self.line_count().saturating_sub(1)
+    }
+
+    /// Get the number of characters, as Unicode scalar values.
     pub fn char_count(&self) -> usize {
         self.buf.len_chars()
     }
 
-    pub fn line(&self, index: usize) -> Cow<str> {
+    /// Get the number of graphemes, which is the number of characters
+    /// as perceived by users, such as an `e` with a combining accent
+    /// counting as one.
+    pub fn grapheme_count(&self) -> usize {
+        // Graphemes never span line breaks.
+        self.buf
+            .lines()
+            .map(|line| Cow::from(line).graphemes(true).count())
+            .sum()
+    }
+
+    /// Get an iterator over the lines, without their line breaks.
+    ///
+    /// ```
+    /// use minime::editor::Editor;
+    ///
+    /// let editor = Editor::from("one\ntwo\nthree");
+    /// let lines: Vec<_> = editor.lines().collect();
+    /// assert_eq!(lines, ["one", "two", "three"]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = Cow<'_, str>> + '_ {
+        self.buf.lines().map(|line| trimmed(line).into())
+    }
+
+    pub fn line(&self, index: usize) -> Cow<'_, str> {
         trimmed(self.buf.line(index)).into()
     }
 
+    /// Get the length of a line given an index.
+    pub fn line_len(&self, index: usize) -> usize {
+        trimmed(self.buf.line(index)).len_chars()
+    }
+
+    /// Get the column of the grapheme boundary after a column
+    /// of the line given an index, or the length of the line.
+    pub(crate) fn next_grapheme(&self, line_idx: usize, col: usize) -> usize {
+        self.grapheme_cols(line_idx)
+            .into_iter()
+            .find(|&boundary| boundary > col)
+            .unwrap_or_else(|| self.line_len(line_idx))
+    }
+
+    /// Get the column of the grapheme boundary before a column
+    /// of the line given an index, or 0.
+    pub(crate) fn prev_grapheme(&self, line_idx: usize, col: usize) -> usize {
+        self.grapheme_cols(line_idx)
+            .into_iter()
+            .take_while(|&boundary| boundary < col)
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// Get the columns where each grapheme of the line given an index starts.
+    fn grapheme_cols(&self, line_idx: usize) -> Vec<usize> {
+        let mut col = 0;
+        self.line(line_idx)
+            .graphemes(true)
+            .map(|grapheme| {
+                let start = col;
+                col += grapheme.chars().count();
+                start
+            })
+            .collect()
+    }
+
     // pub fn insert_line(&mut self, line_idx: usize, string: &str) {
     //     let line_start = self.buf.line_to_char(line_idx);
     //     self.buf.insert(line_start, &string);
     // }
 
-    #[cfg(feature = "unstable")]
+    /// Remove the line given an index, returning it with its line break.
     pub fn remove_line(&mut self, line_idx: usize) -> String {
+        if self.read_only {
+            return String::new();
+        }
         let line_start = self.buf.line_to_char(line_idx);
         let line_end = self.buf.line_to_char(line_idx + 1);
         let rm = self.buf.line(line_idx).to_string();
-        self.buf.remove(line_start..line_end);
+        self.rope_remove(line_start..line_end);
 
         if self.selection.focus.ln == line_idx {
             self.selection.focus.col = 0;
         }
 
-        return rm;
+        rm
+    }
+
+    /// Delete the current line with its line break, or with the line
+    /// break before it if it is the last line.
+    pub fn delete_line(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.selection.anchor = None;
+        let ln = self.selection.focus.ln.min(self.last_line());
+        let end = self.buf.line_to_char(ln + 1);
+        let start = if ln + 1 == self.line_count() && ln > 0 {
+            self.buf.line_to_char(ln) - 1
+        } else {
+            self.buf.line_to_char(ln)
+        };
+        self.rope_remove(start..end);
+        self.selection.focus = Cursor {
+            ln: ln.min(self.last_line()),
+            col: 0,
+        };
     }
 
     // pub fn push_line_str(&mut self, line_idx: usize, string: &str) {
@@ -96,97 +898,766 @@ impl Editor {
         self.selection.focus.col = self.selection.focus.col.min(self.curr_ln_len());
     }
 
+    /// Clamp a cursor into the valid range of the buffer.
+    pub fn clamp_cursor(&self, cursor: Cursor) -> Cursor {
+        let ln = cursor.ln.min(self.last_line());
+        let col = cursor.col.min(self.line_len(ln));
+        Cursor { ln, col }
+    }
+
     /// Get the length of the current line.
     pub fn curr_ln_len(&self) -> usize {
         trimmed(self.buf.line(self.selection.focus.ln)).len_chars()
     }
 
+    /// Check if the focus is at the start of the current line.
+    pub fn at_line_start(&self) -> bool {
+        self.selection.focus.col.min(self.curr_ln_len()) == 0
+    }
+
+    /// Check if the focus is at the end of the current line.
+    pub fn at_line_end(&self) -> bool {
+        let len = self.curr_ln_len();
+        self.selection.focus.col.min(len) == len
+    }
+
+    /// Check if the focus is at the end of the buffer.
+    pub fn at_buffer_end(&self) -> bool {
+        self.selection.focus.ln + 1 >= self.line_count() && self.at_line_end()
+    }
+
     /// Get a character iterator of the current line.
     pub fn curr_ln_chars(&self) -> impl Iterator<Item = char> + '_ {
         trimmed(self.buf.line(self.selection.focus.ln)).chars()
     }
 
     /// Get the current line.
-    pub fn curr_ln(&self) -> Cow<str> {
+    pub fn curr_ln(&self) -> Cow<'_, str> {
         Cow::from(trimmed(self.buf.line(self.selection.focus.ln)))
     }
 
     /// Get the current selection of text.
-    pub fn curr_sel(&self) -> Option<Cow<str>> {
-        if let Some(anchor) = self.selection.anchor {
-            let anchor_idx = self.rope_idx(anchor, 0);
-            let focus_idx = self.rope_idx(self.selection.focus, 0);
-            let slice = if focus_idx < anchor_idx {
-                self.buf.slice(focus_idx..anchor_idx)
-            } else {
-                self.buf.slice(anchor_idx..focus_idx)
-            };
-            Some(Cow::from(trimmed(slice)))
+    pub fn curr_sel(&self) -> Option<Cow<'_, str>> {
+        self.selection_slice().map(Cow::from)
+    }
+
+    /// Get the start and end of the current selection, in order.
+    pub fn selection_range(&self) -> Option<(Cursor, Cursor)> {
+        let anchor = self.clamp_cursor(self.selection.anchor?);
+        let focus = self.clamp_cursor(self.selection.focus);
+        Some((anchor.min(focus), anchor.max(focus)))
+    }
+
+    /// Select the text between two cursors, clamped to the buffer.
+    /// The focus is where the cursor is placed.
+    pub fn set_selection(&mut self, anchor: Cursor, focus: Cursor) {
+        self.selection.anchor = Some(self.clamp_cursor(anchor));
+        self.selection.focus = self.clamp_cursor(focus);
+        self.selection.fix_anchor();
+    }
+
+    /// Get a character iterator of the current selection.
+    pub fn chars_in_selection(&self) -> Option<impl Iterator<Item = char> + '_> {
+        self.selection_slice().map(|slice| slice.chars())
+    }
+
+    fn selection_slice(&self) -> Option<RopeSlice<'_>> {
+        let anchor = self.selection.anchor?;
+        let anchor_idx = self.rope_idx(anchor);
+        let focus_idx = self.rope_idx(self.selection.focus);
+        let slice = if focus_idx < anchor_idx {
+            self.buf.slice(focus_idx..anchor_idx)
         } else {
-            None
-        }
+            self.buf.slice(anchor_idx..focus_idx)
+        };
+        Some(trimmed(slice))
     }
 
     /// Get the character the focus is pointed at.
     pub fn curr_char(&self) -> char {
-        self.buf.char(self.rope_idx(self.selection.focus, 0))
+        self.buf.char(self.rope_idx(self.selection.focus))
+    }
+
+    /// Find the bracket matching the one the focus is pointed at,
+    /// respecting nesting. Returns `None` if the focus is not on a
+    /// bracket or the bracket is unbalanced.
+    pub fn matching_bracket(&self) -> Option<Cursor> {
+        let idx = self.rope_idx(self.clamp_cursor(self.selection.focus));
+        if idx >= self.buf.len_chars() {
+            return None;
+        }
+        let (open, close, forward) = match self.buf.char(idx) {
+            '(' => ('(', ')', true),
+            '[' => ('[', ']', true),
+            '{' => ('{', '}', true),
+            ')' => (')', '(', false),
+            ']' => (']', '[', false),
+            '}' => ('}', '{', false),
+            _ => return None,
+        };
+
+        let mut depth = 0usize;
+        let mut i = if forward { idx } else { idx + 1 };
+        let mut chars = self.buf.chars_at(i);
+        loop {
+            let (c, at) = if forward {
+                let c = chars.next()?;
+                i += 1;
+                (c, i - 1)
+            } else {
+                let c = chars.prev()?;
+                i -= 1;
+                (c, i)
+            };
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(self.cursor_at(at));
+                }
+            }
+        }
+    }
+
+    /// Move the focus to the bracket matching the one it is pointed at.
+    pub fn move_to_matching_bracket(&mut self, anchored: bool) {
+        if let Some(cursor) = self.matching_bracket() {
+            self.selection.set_anchor(anchored);
+            self.selection.focus = cursor;
+            self.selection.fix_anchor();
+        }
     }
 
     fn delete_ln_range(&mut self, start: usize, end: usize) {
+        if self.read_only {
+            return;
+        }
         let idx = self.buf.line_to_char(self.selection.focus.ln);
-        self.buf.remove((idx + start)..(idx + end));
+        self.rope_remove((idx + start)..(idx + end));
         if self.selection.focus.col >= end {
             self.selection.focus.col -= end - start;
+        } else if self.selection.focus.col > start {
+            self.selection.focus.col = start;
         }
     }
 
     fn delete_selection(&mut self, focus: Cursor, anchor: Cursor) {
-        let anchor_idx = self.rope_idx(anchor, 0);
-        let focus_idx = self.rope_idx(focus, 0);
+        let anchor_idx = self.rope_idx(anchor);
+        let focus_idx = self.rope_idx(focus);
         if focus_idx < anchor_idx {
-            self.buf.remove(focus_idx..anchor_idx)
+            self.rope_remove(focus_idx..anchor_idx)
         } else {
-            self.selection.focus = self.selection.anchor.unwrap();
-            self.buf.remove(anchor_idx..focus_idx)
+            self.rope_remove(anchor_idx..focus_idx);
+            self.selection.focus = anchor;
         }
         self.selection.anchor = None;
     }
 
     /// Execute a backspace.
     pub fn backspace(&mut self) {
+        if self.read_only {
+            return;
+        }
+        if let Some((lines, cols)) = self.block() {
+            if cols.start < cols.end {
+                self.replace_block(lines, cols, "");
+            } else if cols.start > 0 {
+                // Removes a wide character that ends at the block too.
+                self.replace_block(lines, cols.start - 1..cols.start, "");
+            }
+            return;
+        }
         self.clamp();
 
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
+        } else if let Some(start) = self.indent_stop_before() {
+            self.delete_ln_range(start, self.selection.focus.col);
         } else if self.selection.focus.col > 0 {
-            self.delete_char(-1);
-            self.selection.focus.col -= 1;
+            // Remove the whole grapheme, such as a flag made of two characters.
+            let Cursor { ln, col } = self.selection.focus;
+            let start = Cursor {
+                ln,
+                col: self.prev_grapheme(ln, col),
+            };
+            self.rope_remove(self.rope_idx(start)..self.rope_idx(self.selection.focus));
+            self.selection.focus = start;
         } else if self.selection.focus.ln > 0 {
-            let col = self.buf.line(self.selection.focus.ln - 1).len_chars();
+            let col = self.line_len(self.selection.focus.ln - 1);
             self.delete_char(-1);
             self.selection.focus.ln -= 1;
-            self.selection.focus.col = col - 1;
+            self.selection.focus.col = col;
+        }
+    }
+
+    /// Get the column of the indentation level before the focus, if smart
+    /// backspace is set and only spaces are before the focus.
+    fn indent_stop_before(&self) -> Option<usize> {
+        let col = self.selection.focus.col;
+        if !self.smart_backspace || col < 2 {
+            return None;
         }
+        if !self.curr_ln_chars().take(col).all(|c| c == ' ') {
+            return None;
+        }
+        Some((col - 1) / self.tab_width * self.tab_width)
     }
 
     /// Execute a delete.
     pub fn delete(&mut self) {
-        self.clamp();
-
-        if let Some(anchor) = self.selection.anchor {
+        if self.read_only {
+            return;
+        }
+        if let Some((lines, cols)) = self.block() {
+            if cols.start < cols.end {
+                self.replace_block(lines, cols, "");
+            } else {
+                self.replace_block(lines, cols.start..cols.start + 1, "");
+            }
+            return;
+        }
+        self.clamp();
+
+        if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
-        } else if self.selection.focus.col < self.curr_ln_len() || self.selection.focus.ln + 1 < self.line_count() {
+        } else if self.selection.focus.col < self.curr_ln_len() {
+            let Cursor { ln, col } = self.selection.focus;
+            let end = Cursor {
+                ln,
+                col: self.next_grapheme(ln, col),
+            };
+            self.rope_remove(self.rope_idx(self.selection.focus)..self.rope_idx(end));
+        } else if self.selection.focus.ln + 1 < self.line_count() {
+            // The focus is clamped, so at the end of a line it indexes the
+            // line break, and removing it joins the next line onto this one.
             self.delete_char(0);
         }
     }
 
+    /// Delete the word before the cursor.
+    pub fn backspace_word(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+
+        if self.selection.anchor.is_some() || self.selection.focus.col == 0 {
+            self.backspace();
+        } else {
+            let start = self.word_start_before(self.selection.focus.col);
+            self.delete_ln_range(start, self.selection.focus.col);
+        }
+    }
+
+    /// Delete the word after the cursor.
+    pub fn delete_word(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+
+        if self.selection.anchor.is_some() || self.at_line_end() {
+            self.delete();
+        } else {
+            let end = self.word_end_after(self.selection.focus.col);
+            self.delete_ln_range(self.selection.focus.col, end);
+        }
+    }
+
+    /// Get the column of the start of the word before a column
+    /// of the current line, skipping whitespace.
+    fn word_start_before(&self, col: usize) -> usize {
+        let chars: Vec<char> = self.curr_ln_chars().take(col).collect();
+        let mut start = chars.len();
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        if let Some(&c) = start.checked_sub(1).and_then(|i| chars.get(i)) {
+            let class = char_class(c);
+            while start > 0 && char_class(chars[start - 1]) == class {
+                start -= 1;
+            }
+        }
+        start
+    }
+
+    /// Get the column of the end of the word after a column
+    /// of the current line, skipping whitespace.
+    fn word_end_after(&self, col: usize) -> usize {
+        let chars: Vec<char> = self.curr_ln_chars().collect();
+        let mut end = col.min(chars.len());
+        while end < chars.len() && chars[end].is_whitespace() {
+            end += 1;
+        }
+        if let Some(&c) = chars.get(end) {
+            let class = char_class(c);
+            while end < chars.len() && char_class(chars[end]) == class {
+                end += 1;
+            }
+        }
+        end
+    }
+
+    /// Select the word around the focus. Prefers the word before the focus
+    /// when it sits between a word and whitespace or the end of the line.
+    pub fn select_word(&mut self) {
+        let chars: Vec<char> = self.curr_ln_chars().collect();
+        let col = self.selection.focus.col.min(chars.len());
+        let at = |i: usize| chars.get(i).map_or(0, |&c| char_class(c));
+        let i = if col > 0 && at(col) == 0 && at(col - 1) != 0 {
+            col - 1
+        } else if col < chars.len() {
+            col
+        } else if col > 0 {
+            col - 1
+        } else {
+            return;
+        };
+        let class = char_class(chars[i]);
+        let mut start = i;
+        while start > 0 && char_class(chars[start - 1]) == class {
+            start -= 1;
+        }
+        let mut end = i + 1;
+        while end < chars.len() && char_class(chars[end]) == class {
+            end += 1;
+        }
+        let ln = self.selection.focus.ln;
+        self.set_selection(Cursor { ln, col: start }, Cursor { ln, col: end });
+    }
+
+    /// Replace every character of the selection, or of the word around
+    /// the focus if there is no selection, with the characters mapped by
+    /// a function. A character can map to several characters, such as
+    /// `ß` to `SS` with `char::to_uppercase`.
+    ///
+    /// A selection is kept around the new text, otherwise the focus is
+    /// moved to the end of the word. Nothing is changed if the new text
+    /// would exceed the maximum number of characters.
+    pub fn transform_selection<I>(&mut self, f: impl Fn(char) -> I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        if self.read_only {
+            return;
+        }
+        let selected = self.selection.anchor.is_some();
+        let (start, end) = match self.selection_range() {
+            Some(range) => range,
+            None => {
+                let focus = self.selection.focus;
+                self.select_word();
+                match self.selection_range() {
+                    Some(range) => range,
+                    None => {
+                        self.selection.focus = focus;
+                        return;
+                    }
+                }
+            }
+        };
+        let forward = self.clamp_cursor(self.selection.focus) == end;
+        let start_idx = self.rope_idx(start);
+        let end_idx = self.rope_idx(end);
+        let text = self.buf.slice(start_idx..end_idx).to_string();
+        let new: String = text.chars().flat_map(f).collect();
+        let old_len = end_idx - start_idx;
+        let new_len = new.chars().count();
+        if new == text || !self.fits_chars(new_len.saturating_sub(old_len)) {
+            if !selected {
+                self.selection.anchor = None;
+                self.selection.focus = end;
+            }
+            return;
+        }
+
+        self.rope_remove(start_idx..end_idx);
+        self.rope_insert(start_idx, &new);
+        let end = self.cursor_at(start_idx + new_len);
+        if !selected {
+            self.selection.anchor = None;
+            self.selection.focus = end;
+        } else if forward {
+            self.set_selection(start, end);
+        } else {
+            self.set_selection(end, start);
+        }
+    }
+
+    /// Convert the selection, or the word around the focus, to uppercase.
+    pub fn uppercase_selection(&mut self) {
+        self.transform_selection(char::to_uppercase);
+    }
+
+    /// Convert the selection, or the word around the focus, to lowercase.
+    pub fn lowercase_selection(&mut self) {
+        self.transform_selection(char::to_lowercase);
+    }
+
+    /// Swap the case of every character of the selection,
+    /// or of the word around the focus.
+    pub fn toggle_case_selection(&mut self) {
+        self.transform_selection(|c| -> Vec<char> {
+            if c.is_uppercase() {
+                c.to_lowercase().collect()
+            } else {
+                c.to_uppercase().collect()
+            }
+        });
+    }
+
+    /// Select the current line, including its line break
+    /// unless it is the last line.
+    pub fn select_line(&mut self) {
+        let ln = self.selection.focus.ln;
+        let focus = if ln < self.last_line() {
+            Cursor { ln: ln + 1, col: 0 }
+        } else {
+            Cursor {
+                ln,
+                col: self.line_len(ln),
+            }
+        };
+        self.set_selection(Cursor { ln, col: 0 }, focus);
+    }
+
+    /// Delete from the cursor to the end of the line. At the end of
+    /// the line, the next line is joined instead.
+    pub fn delete_to_line_end(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+        self.selection.anchor = None;
+        let len = self.curr_ln_len();
+        if self.selection.focus.col < len {
+            self.delete_ln_range(self.selection.focus.col, len);
+        } else if self.selection.focus.ln + 1 < self.line_count() {
+            self.delete_char(0);
+        }
+    }
+
+    /// Delete from the start of the line to the cursor.
+    pub fn delete_to_line_start(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+        self.selection.anchor = None;
+        self.delete_ln_range(0, self.selection.focus.col);
+        self.selection.focus.col = 0;
+    }
+
+    /// Swap the character before the cursor with the one at the cursor,
+    /// and move the cursor past both. At the end of the line, the two
+    /// characters before the cursor are swapped instead.
+    pub fn transpose_chars(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+        self.selection.anchor = None;
+        let ln = self.selection.focus.ln;
+        let len = self.curr_ln_len();
+        let mut col = self.selection.focus.col;
+        if col == 0 || len < 2 {
+            return;
+        }
+        if col == len {
+            col = self.prev_grapheme(ln, len);
+        }
+        let prev = self.prev_grapheme(ln, col);
+        let next = self.next_grapheme(ln, col);
+        if prev == col || col == next {
+            return;
+        }
+
+        let line = self.line(ln);
+        let before: String = line.chars().skip(prev).take(col - prev).collect();
+        let at: String = line.chars().skip(col).take(next - col).collect();
+        let idx = self.buf.line_to_char(ln);
+        self.rope_remove(idx + prev..idx + next);
+        self.rope_insert(idx + prev, &(at + &before));
+        self.selection.focus.col = next;
+    }
+
+    /// Copy the selection, or the current line if there is no
+    /// selection, into the internal register.
+    pub fn yank(&mut self) {
+        self.register = match self.curr_sel() {
+            Some(sel) => sel.into_owned(),
+            None => self.curr_ln().into_owned(),
+        };
+        self.copied = Some(self.register.clone());
+    }
+
+    /// Insert the contents of the internal register at the cursor.
+    pub fn paste_register(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let register = self.register.clone();
+        self.insert_str(&register);
+    }
+
+    /// Set the contents of the internal register.
+    pub fn set_register(&mut self, text: impl Into<String>) {
+        self.register = text.into();
+    }
+
+    /// Get the contents of the internal register.
+    pub fn register(&self) -> &str {
+        &self.register
+    }
+
+    /// Remove trailing spaces and tabs from every line.
+    pub fn trim_trailing_whitespace(&mut self) {
+        if self.read_only {
+            return;
+        }
+        for ln in 0..self.line_count() {
+            let line = self.line(ln);
+            let len = line.chars().count();
+            let trimmed_len = line.trim_end_matches([' ', '\t']).chars().count();
+            if trimmed_len < len {
+                let idx = self.buf.line_to_char(ln);
+                self.rope_remove(idx + trimmed_len..idx + len);
+            }
+        }
+        self.selection.focus = self.clamp_cursor(self.selection.focus);
+        self.selection.anchor = self.selection.anchor.map(|c| self.clamp_cursor(c));
+        self.selection.fix_anchor();
+    }
+
+    /// Duplicate the selection, or the current line if there is no
+    /// selection. The focus moves onto the duplicate.
+    pub fn duplicate_line(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.clamp();
+
+        if let Some(anchor) = self.selection.anchor {
+            let anchor_idx = self.rope_idx(anchor);
+            let focus_idx = self.rope_idx(self.selection.focus);
+            let (start, end) = (anchor_idx.min(focus_idx), anchor_idx.max(focus_idx));
+            let text = self.buf.slice(start..end).to_string();
+            let lines = text.matches('\n').count();
+            if !self.fits_lines(lines) || !self.fits_chars(end - start) {
+                return;
+            }
+            self.rope_insert(end, &text);
+            self.selection.anchor = Some(self.cursor_at(end));
+            self.selection.focus = self.cursor_at(end + (end - start));
+        } else if self.fits_lines(1) && self.fits_chars(self.curr_ln_len() + 1) {
+            let line = self.curr_ln().into_owned();
+            let end = Cursor {
+                ln: self.selection.focus.ln,
+                col: self.curr_ln_len(),
+            };
+            let idx = self.rope_idx(end);
+            self.rope_insert(idx, &format!("\n{}", line));
+            self.selection.focus.ln += 1;
+        }
+    }
+
+    /// Join the current line with the next line, or all selected lines
+    /// into one line. Leading whitespace of joined lines is collapsed
+    /// into a single space, and the focus lands at the last join point.
+    pub fn join_lines(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let (start, end) = match self.selection.anchor.take() {
+            Some(anchor) => {
+                let focus = self.selection.focus;
+                (focus.ln.min(anchor.ln), focus.ln.max(anchor.ln))
+            }
+            None => (self.selection.focus.ln, self.selection.focus.ln + 1),
+        };
+        let end = end.min(self.last_line());
+        for _ in start..end {
+            let col = self.join_line(start);
+            self.selection.focus = Cursor { ln: start, col };
+        }
+    }
+
+    /// Join a line with the next line, returning the column of the join.
+    fn join_line(&mut self, ln: usize) -> usize {
+        let line = self.line(ln).into_owned();
+        let next = self.line(ln + 1).into_owned();
+        let indent = next.chars().take_while(|c| c.is_whitespace()).count();
+        let col = line.chars().count();
+
+        let idx = self.buf.line_to_char(ln) + col;
+        self.rope_remove(idx..idx + 1 + indent);
+        if !line.trim_end().is_empty() && indent < next.chars().count() {
+            self.rope_insert(idx, " ");
+        }
+        col
+    }
+
+    /// Comment out the current line or the selected lines by inserting a
+    /// prefix after the indentation, or uncomment them if they all are.
+    /// Blank lines are left alone.
+    pub fn toggle_line_comment(&mut self, prefix: &str) {
+        if self.read_only {
+            return;
+        }
+        let lines = self.selected_lines();
+        let comment = lines.clone().any(|ln| {
+            let line = self.line(ln);
+            let text = line.trim_start();
+            !text.is_empty() && !text.starts_with(prefix)
+        });
+        if comment && !self.fits_chars(lines.len() * (prefix.chars().count() + 1)) {
+            return;
+        }
+        for ln in lines {
+            let line = self.line(ln).into_owned();
+            let text = line.trim_start();
+            if text.is_empty() {
+                continue;
+            }
+            let indent = line[..line.len() - text.len()].chars().count();
+            let idx = self.buf.line_to_char(ln) + indent;
+            if comment {
+                let inserted = format!("{} ", prefix);
+                self.rope_insert(idx, &inserted);
+                self.shift_cols(ln, indent, inserted.chars().count() as isize);
+            } else {
+                let mut len = prefix.chars().count();
+                if text[prefix.len()..].starts_with(' ') {
+                    len += 1;
+                }
+                self.rope_remove(idx..idx + len);
+                self.shift_cols(ln, indent, -(len as isize));
+            }
+        }
+    }
+
+    /// Check if the selection spans multiple lines.
+    pub fn selects_lines(&self) -> bool {
+        self.selection
+            .anchor
+            .is_some_and(|anchor| anchor.ln != self.selection.focus.ln)
+    }
+
+    /// Insert an indentation at the start of each selected line,
+    /// or the current line. Blank lines are left alone.
+    pub fn indent_selection(&mut self, indent: &str) {
+        if self.read_only {
+            return;
+        }
+        let lines = self.selected_lines();
+        if !self.fits_chars(lines.len() * indent.chars().count()) {
+            return;
+        }
+        for ln in lines {
+            if self.line_len(ln) == 0 {
+                continue;
+            }
+            self.rope_insert(self.buf.line_to_char(ln), indent);
+            self.shift_cols(ln, 0, indent.chars().count() as isize);
+        }
+    }
+
+    /// Remove a level of indentation from each selected line, or the current
+    /// line. That is a leading tab, or up to a tab width of leading spaces.
+    pub fn dedent_selection(&mut self) {
+        if self.read_only {
+            return;
+        }
+        for ln in self.selected_lines() {
+            let line = self.buf.line(ln);
+            let len = if line.chars().next() == Some('\t') {
+                1
+            } else {
+                line.chars()
+                    .take(self.tab_width)
+                    .take_while(|&c| c == ' ')
+                    .count()
+            };
+            if len > 0 {
+                let idx = self.buf.line_to_char(ln);
+                self.rope_remove(idx..idx + len);
+                self.shift_cols(ln, 0, -(len as isize));
+            }
+        }
+    }
+
+    /// Get the lines touched by the selection, or the current line.
+    /// A selection ending at the start of a line does not include it.
+    fn selected_lines(&self) -> Range<usize> {
+        let focus = self.selection.focus;
+        match self.selection.anchor {
+            Some(anchor) => {
+                let (start, end) = (focus.min(anchor), focus.max(anchor));
+                if end.col == 0 && end.ln > start.ln {
+                    start.ln..end.ln
+                } else {
+                    start.ln..end.ln + 1
+                }
+            }
+            None => focus.ln..focus.ln + 1,
+        }
+    }
+
+    /// Shift the focus and anchor on a line if they are at or after a
+    /// column, after text was inserted or removed there.
+    fn shift_cols(&mut self, ln: usize, col: usize, delta: isize) {
+        let cursors = once(&mut self.selection.focus).chain(self.selection.anchor.as_mut());
+        for cursor in cursors {
+            if cursor.ln == ln && cursor.col >= col {
+                cursor.col = cursor.col.saturating_add_signed(delta).max(col);
+            }
+        }
+    }
+
+    /// Swap the current line with the line above it.
+    pub fn move_line_up(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let ln = self.selection.focus.ln;
+        if ln > 0 {
+            self.swap_lines(ln - 1);
+            self.selection.focus.ln -= 1;
+        }
+    }
+
+    /// Swap the current line with the line below it.
+    pub fn move_line_down(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let ln = self.selection.focus.ln;
+        if ln + 1 < self.line_count() {
+            self.swap_lines(ln);
+            self.selection.focus.ln += 1;
+        }
+    }
+
+    /// Swap a line with the line below it.
+    fn swap_lines(&mut self, ln: usize) {
+        self.selection.anchor = None;
+        let first = self.line(ln).into_owned();
+        let second = self.line(ln + 1).into_owned();
+        let start = self.buf.line_to_char(ln);
+        let end = start + first.chars().count() + 1 + second.chars().count();
+        self.rope_remove(start..end);
+        self.rope_insert(start, &format!("{}\n{}", second, first));
+    }
+
     /// Move the cursor right.
     pub fn move_right(&mut self, anchored: bool) {
         self.clamp();
         self.selection.set_anchor(anchored);
         let len = self.curr_ln_len();
         if self.selection.focus.col < len {
-            self.selection.focus.col += 1;
+            let Cursor { ln, col } = self.selection.focus;
+            self.selection.focus.col = self.next_grapheme(ln, col);
         } else if self.selection.focus.ln + 1 < self.line_count() {
             // Move to the beginning of the next line.
             self.selection.focus.ln += 1;
@@ -200,7 +1671,8 @@ impl Editor {
         self.clamp();
         self.selection.set_anchor(anchored);
         if self.selection.focus.col > 0 {
-            self.selection.focus.col -= 1;
+            let Cursor { ln, col } = self.selection.focus;
+            self.selection.focus.col = self.prev_grapheme(ln, col);
         } else if self.selection.focus.ln > 0 {
             // Move to the end of the previous line.
             self.selection.focus.ln -= 1;
@@ -231,6 +1703,37 @@ impl Editor {
         self.selection.fix_anchor();
     }
 
+    /// Move the cursor to the previous blank line.
+    pub fn move_paragraph_up(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        let mut ln = self.selection.focus.ln;
+        while ln > 0 && self.line_len(ln) == 0 {
+            ln -= 1;
+        }
+        while ln > 0 && self.line_len(ln) != 0 {
+            ln -= 1;
+        }
+        self.selection.focus.ln = ln;
+        self.selection.focus.col = 0;
+        self.selection.fix_anchor();
+    }
+
+    /// Move the cursor to the next blank line.
+    pub fn move_paragraph_down(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        let last = self.last_line();
+        let mut ln = self.selection.focus.ln;
+        while ln < last && self.line_len(ln) == 0 {
+            ln += 1;
+        }
+        while ln < last && self.line_len(ln) != 0 {
+            ln += 1;
+        }
+        self.selection.focus.ln = ln;
+        self.selection.focus.col = if ln == last { self.curr_ln_len() } else { 0 };
+        self.selection.fix_anchor();
+    }
+
     /// Move the cursor to a column.
     pub fn move_to_col(&mut self, col: usize, anchored: bool) {
         self.selection.set_anchor(anchored);
@@ -238,6 +1741,30 @@ impl Editor {
         self.selection.fix_anchor();
     }
 
+    /// Move the cursor to a line, clamped to the buffer.
+    pub fn move_to_line(&mut self, ln: usize, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        self.selection.focus.ln = ln.min(self.last_line());
+        self.clamp();
+        self.selection.fix_anchor();
+    }
+
+    /// Move the cursor up by the number of lines shown in the last frame.
+    pub fn page_up(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        let page = self.viewport().len().max(1);
+        self.selection.focus.ln = self.selection.focus.ln.saturating_sub(page);
+        self.selection.fix_anchor();
+    }
+
+    /// Move the cursor down by the number of lines shown in the last frame.
+    pub fn page_down(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        let page = self.viewport().len().max(1);
+        self.selection.focus.ln = (self.selection.focus.ln + page).min(self.last_line());
+        self.selection.fix_anchor();
+    }
+
     /// Move the cursor to the top of the buffer.
     pub fn move_to_top(&mut self) {
         self.selection.focus.ln = 0;
@@ -245,7 +1772,23 @@ impl Editor {
 
     /// Move the cursor to the bottom of the buffer.
     pub fn move_to_bottom(&mut self) {
-        self.selection.focus.ln = self.line_count() - 1;
+        self.selection.focus.ln = self.last_line();
+    }
+
+    /// Move the cursor to the first non-whitespace character of the line,
+    /// or to the start of the line if it is already there.
+    pub fn move_home(&mut self, anchored: bool) {
+        let indent = self
+            .curr_ln_chars()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        // The focus may be past the end of a shorter line.
+        let col = self.selection.focus.col.min(self.curr_ln_len());
+        if col == indent {
+            self.move_to_col(0, anchored);
+        } else {
+            self.move_to_col(indent, anchored);
+        }
     }
 
     /// Move the cursor to the end of the current line.
@@ -254,53 +1797,947 @@ impl Editor {
     }
 
     /// Delete a character offset from the cursor.
+    /// Does nothing if the offset is outside of the buffer.
     pub fn delete_char(&mut self, offset: isize) {
-        let z = self.rope_idx(self.selection.focus, offset);
-        self.buf.remove(z..=z);
+        if self.read_only {
+            return;
+        }
+        if let Some(z) = self.offset_idx(self.selection.focus, offset) {
+            if z < self.buf.len_chars() {
+                self.rope_remove(z..z + 1);
+            }
+        }
     }
 
+    /// Insert a character offset from the cursor.
+    /// Does nothing if the offset is outside of the buffer.
     pub fn insert_char(&mut self, offset: isize, c: char) {
-        let z = self.rope_idx(self.selection.focus, offset);
-        self.buf.insert_char(z, c);
+        if self.read_only {
+            return;
+        }
+        if !self.fits_chars(1) {
+            return;
+        }
+        if let Some(z) = self.offset_idx(self.selection.focus, offset) {
+            self.rope_insert(z, c.encode_utf8(&mut [0; 4]));
+        }
     }
 
     /// Type a character at the cursor.
     pub fn type_char(&mut self, c: char) {
+        if self.read_only {
+            return;
+        }
+        if c != '\n' {
+            if let Some((lines, cols)) = self.block() {
+                if !self.fits_chars(lines.len()) {
+                    return;
+                }
+                self.replace_block(lines, cols, c.encode_utf8(&mut [0; 4]));
+                return;
+            }
+        }
         self.clamp();
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
         }
+        if (c == '\n' && !self.fits_lines(1)) || !self.fits_chars(1) {
+            return;
+        }
         self.insert_char(0, c);
         if c == '\n' {
             self.selection.focus.col = 0;
             self.selection.focus.ln += 1;
         } else {
-            self.selection.focus.col = self.selection.focus.col + 1;
+            self.selection.focus.col += 1;
+        }
+    }
+
+    /// Get the lines and terminal cells covered by a block selection.
+    ///
+    /// The block spans the same cells on every line, so that it stays
+    /// rectangular on lines with tabs or wide characters.
+    pub(crate) fn block(&self) -> Option<(Range<usize>, Range<usize>)> {
+        if !self.selection.block {
+            return None;
+        }
+        let anchor = self.selection.anchor?;
+        let focus = self.selection.focus;
+        let lines = anchor.ln.min(focus.ln)..anchor.ln.max(focus.ln) + 1;
+        let anchor_cell = self.display_col(anchor.ln, anchor.col, true);
+        let focus_cell = self.display_col(focus.ln, focus.col, true);
+        let cells = anchor_cell.min(focus_cell)..anchor_cell.max(focus_cell);
+        Some((lines, cells))
+    }
+
+    /// Replace the cells of every line of a block selection with text,
+    /// leaving an empty block after the text. Lines too short to reach
+    /// the cells are left alone.
+    fn replace_block(&mut self, lines: Range<usize>, cells: Range<usize>, text: &str) {
+        let chars = text.chars().count();
+        let mut focus = self.selection.focus;
+        let mut anchor = self.selection.anchor;
+        for ln in lines {
+            let cols = match self.block_cols(ln, cells.clone()) {
+                Some(cols) => cols,
+                None => continue,
+            };
+            let idx = self.buf.line_to_char(ln);
+            if cols.start < cols.end {
+                self.rope_remove(idx + cols.start..idx + cols.end);
+            }
+            if !text.is_empty() {
+                self.rope_insert(idx + cols.start, text);
+            }
+            for cursor in std::iter::once(&mut focus).chain(anchor.as_mut()) {
+                if cursor.ln == ln {
+                    cursor.col = cols.start + chars;
+                }
+            }
         }
+        self.selection.focus = focus;
+        self.selection.anchor = anchor;
+        self.selection.fix_anchor();
     }
 
     /// Insert a string at the cursor.
     pub(crate) fn insert_str(&mut self, str: &str) {
+        if self.read_only {
+            return;
+        }
         self.clamp();
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
         }
-        let z = self.rope_idx(self.selection.focus, 0);
-        self.buf.insert(z, str);
+        let str = self.fit_chars(self.fit_lines(str));
+        let z = self.rope_idx(self.selection.focus);
+        self.rope_insert(z, str);
+        self.selection.focus = self.cursor_at(z + str.chars().count());
+    }
+
+    /// Get the character index of the focus in the content.
+    pub fn focus_char_index(&self) -> usize {
+        self.cursor_to_index(self.selection.focus)
+    }
+
+    /// Get the character index of a cursor in the content,
+    /// clamping the cursor into the buffer.
+    pub fn cursor_to_index(&self, cursor: Cursor) -> usize {
+        self.rope_idx(self.clamp_cursor(cursor))
+    }
+
+    /// Get the cursor at a character index of the content,
+    /// clamping the index into the buffer.
+    pub fn index_to_cursor(&self, idx: usize) -> Cursor {
+        self.cursor_at(idx.min(self.buf.len_chars()))
+    }
+
+    /// Get the cursor at a character index of the buffer.
+    fn cursor_at(&self, idx: usize) -> Cursor {
+        let ln = self.buf.char_to_line(idx);
+        let col = idx - self.buf.line_to_char(ln);
+        Cursor { ln, col }
+    }
+
+    fn rope_idx(&self, cursor: Cursor) -> usize {
+        self.buf.line_to_char(cursor.ln) + cursor.col
+    }
+
+    /// Get the character index offset from a cursor,
+    /// or `None` if it falls outside of the buffer.
+    fn offset_idx(&self, cursor: Cursor, offset: isize) -> Option<usize> {
+        self.rope_idx(cursor)
+            .checked_add_signed(offset)
+            .filter(|&idx| idx <= self.buf.len_chars())
+    }
+}
+
+/// Classify a character for word boundaries.
+fn char_class(c: char) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+/// Check if an event is input from the user, a key press or a paste,
+/// rather than a mouse event, a resize or a change of focus.
+fn is_input(event: &Event) -> bool {
+    match event {
+        Event::Key(key) => key.kind != KeyEventKind::Release,
+        Event::Paste(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::keybindings::NormalKeybinding;
+    use crate::renderer::string::StringRenderer;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    /// Create an editor with the focus at a line and column.
+    fn editor_at(contents: &str, ln: usize, col: usize) -> Editor {
+        let mut editor = Editor::from(contents);
+        editor.selection.focus = Cursor { ln, col };
+        editor
+    }
 
-        let lines = str.lines().count().max(1);
+    #[test]
+    fn predicates_at_buffer_start() {
+        let editor = editor_at("ab\ncd", 0, 0);
+        assert!(editor.at_line_start());
+        assert!(!editor.at_line_end());
+        assert!(!editor.at_buffer_end());
+    }
+
+    #[test]
+    fn predicates_at_buffer_end() {
+        let editor = editor_at("ab\ncd", 1, 2);
+        assert!(!editor.at_line_start());
+        assert!(editor.at_line_end());
+        assert!(editor.at_buffer_end());
+
+        // The end of another line is not the end of the buffer.
+        let editor = editor_at("ab\ncd", 0, 2);
+        assert!(editor.at_line_end());
+        assert!(!editor.at_buffer_end());
+    }
+
+    #[test]
+    fn predicates_on_empty_buffer() {
+        let editor = Editor::default();
+        assert!(editor.at_line_start());
+        assert!(editor.at_line_end());
+        assert!(editor.at_buffer_end());
+
+        // An empty last line is both its start and the end of the buffer.
+        let editor = editor_at("ab\n", 1, 0);
+        assert!(editor.at_line_start());
+        assert!(editor.at_buffer_end());
+    }
+
+    #[test]
+    fn predicates_clamp_the_focus() {
+        let editor = editor_at("ab", 0, 10);
+        assert!(editor.at_line_end());
+        assert!(editor.at_buffer_end());
+    }
+
+    #[test]
+    fn chars_in_selection_match_the_selection() {
+        let mut editor = Editor::from("héllo\nwörld");
+        assert!(editor.chars_in_selection().is_none());
+
+        editor.set_selection(Cursor { ln: 1, col: 3 }, Cursor { ln: 0, col: 1 });
+        let count = editor.chars_in_selection().unwrap().count();
+        assert_eq!(count, editor.curr_sel().unwrap().chars().count());
+        assert_eq!(count, 8);
+        let text: String = editor.chars_in_selection().unwrap().collect();
+        assert_eq!(text, "éllo\nwör");
+    }
+
+    #[test]
+    fn paragraph_motion_skips_consecutive_blank_lines() {
+        let mut editor = editor_at("a\nb\n\n\nc\nd\n\ne", 0, 1);
+        editor.move_paragraph_down(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 0 });
+        editor.move_paragraph_down(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 6, col: 0 });
+
+        editor.move_paragraph_up(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 3, col: 0 });
+        editor.move_paragraph_up(true);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 3, col: 0 }));
+    }
+
+    #[test]
+    fn paragraph_motion_stops_at_buffer_boundaries() {
+        let mut editor = editor_at("ab\ncd", 1, 1);
+        editor.move_paragraph_down(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 2 });
+        editor.move_paragraph_up(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+        editor.move_paragraph_up(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+
+        let mut editor = Editor::default();
+        editor.move_paragraph_down(false);
+        editor.move_paragraph_up(false);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+    }
+
+    #[test]
+    fn set_contents_clamps_the_selection() {
+        let mut editor = Editor::from("first line\nsecond line\nthird line");
+        editor.set_selection(Cursor { ln: 1, col: 3 }, Cursor { ln: 2, col: 8 });
+        editor.set_contents("ab\ncd".as_bytes()).unwrap();
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 2 });
+        // The anchor is clamped onto the focus, leaving no selection.
+        assert_eq!(editor.selection.anchor, None);
+        editor.insert_char(0, 'x');
+        assert_eq!(editor.contents(), "ab\ncdx");
+    }
+
+    #[test]
+    fn delete_joins_lines_at_the_line_end() {
+        let mut editor = editor_at("ab\ncd", 0, 2);
+        editor.delete();
+        assert_eq!(editor.contents(), "abcd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+
+        // A focus past the end of the line is clamped first.
+        let mut editor = editor_at("ab\ncd", 0, 5);
+        editor.delete();
+        assert_eq!(editor.contents(), "abcd");
+    }
+
+    #[test]
+    fn delete_within_a_line_does_not_join() {
+        let mut editor = editor_at("ab\ncd", 0, 1);
+        editor.delete();
+        assert_eq!(editor.contents(), "a\ncd");
+
+        let mut editor = editor_at("ab\ncd", 0, 0);
+        editor.delete();
+        assert_eq!(editor.contents(), "b\ncd");
+    }
+
+    #[test]
+    fn delete_joins_an_empty_line() {
+        let mut editor = editor_at("ab\n\ncd", 1, 0);
+        editor.delete();
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        // Nothing follows the end of the buffer.
+        let mut editor = editor_at("ab\n", 1, 0);
+        editor.delete();
+        assert_eq!(editor.line_count(), 2);
+    }
+
+    /// Insert text at the end of the buffer as its own undo step.
+    fn insert_step(editor: &mut Editor, text: &str) {
+        editor.move_to_bottom();
+        editor.move_to_line_end(false);
+        editor.insert_str(text);
+        editor.commit_history();
+    }
+
+    #[test]
+    fn undo_budget_evicts_the_oldest_steps() {
+        let mut editor = Editor::default();
+        editor.set_undo_limits(None, Some(2500));
+        for c in ['a', 'b', 'c', 'd'] {
+            insert_step(&mut editor, &c.to_string().repeat(1000));
+        }
+        // Only the last two inserts fit in the budget.
+        editor.undo();
+        editor.undo();
+        assert_eq!(editor.char_count(), 2000);
+        editor.undo();
+        assert_eq!(editor.char_count(), 2000);
+        editor.redo();
+        editor.redo();
+        assert_eq!(editor.char_count(), 4000);
+    }
+
+    #[test]
+    fn undo_limits_keep_the_newest_step() {
+        let mut editor = Editor::default();
+        editor.set_undo_limits(None, Some(10));
+        insert_step(&mut editor, "a large paste");
+        editor.undo();
+        assert_eq!(editor.contents(), "");
+
+        let mut editor = Editor::default();
+        editor.set_undo_limits(Some(0), None);
+        insert_step(&mut editor, "ab");
+        insert_step(&mut editor, "cd");
+        editor.undo();
+        assert_eq!(editor.contents(), "ab");
+        editor.undo();
+        assert_eq!(editor.contents(), "ab");
+    }
+
+    #[test]
+    fn status_is_cleared_by_input_only() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+
+        let mut editor = Editor::default();
+        let mut keybinding = NormalKeybinding::default();
+        editor.set_status("Copied");
+        let mouse = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        });
+        editor
+            .replay(&mut keybinding, vec![mouse, Event::Resize(80, 24)])
+            .unwrap();
+        assert_eq!(editor.status(), Some("Copied"));
+
+        let key = Event::Key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        editor.replay(&mut keybinding, vec![key]).unwrap();
+        assert_eq!(editor.status(), None);
+    }
+
+    #[test]
+    fn scroll_is_reset_by_input_only() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+
+        let mouse = |kind| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let mut editor = Editor::from("0\n1\n2\n3\n4\n5\n6\n7\n8\n9");
+        let mut keybinding = NormalKeybinding::default();
+        let events = vec![
+            mouse(MouseEventKind::ScrollDown),
+            mouse(MouseEventKind::Moved),
+            mouse(MouseEventKind::ScrollDown),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.scroll_top(), Some(6));
+
+        let key = Event::Key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        editor.replay(&mut keybinding, vec![key]).unwrap();
+        assert_eq!(editor.scroll_top(), None);
+    }
+
+    #[test]
+    fn duplicate_first_line() {
+        let mut editor = editor_at("ab\ncd", 0, 1);
+        editor.duplicate_line();
+        assert_eq!(editor.contents(), "ab\nab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 1 });
+    }
 
-        self.selection.focus.ln += lines - 1;
-        self.selection.focus.col = self.selection.focus.col + str.lines().last().unwrap_or_default().len();
+    #[test]
+    fn duplicate_last_line() {
+        let mut editor = editor_at("ab\ncd", 1, 2);
+        editor.duplicate_line();
+        assert_eq!(editor.contents(), "ab\ncd\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 2 });
     }
 
-    fn rope_idx(&self, cursor: Cursor, offset: isize) -> usize {
-        let idx = cursor.col;
-        let line_start = self.buf.line_to_char(cursor.ln);
-        let z = line_start + idx;
-        // Take good care to not underflow subtract.
-        // Also I'm keeping this and assuming that
-        // all machines will use Two's Complement.
-        z.wrapping_add(offset as usize)
+    #[test]
+    fn duplicate_multi_line_selection() {
+        let mut editor = Editor::from("ab\ncd\nef");
+        editor.set_selection(Cursor { ln: 0, col: 1 }, Cursor { ln: 1, col: 1 });
+        editor.duplicate_line();
+        assert_eq!(editor.contents(), "ab\ncb\ncd\nef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 1, col: 1 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 1 });
+    }
+
+    #[test]
+    fn move_lines_up_and_down() {
+        let mut editor = editor_at("a\nbc\nd", 1, 2);
+        editor.move_line_up();
+        assert_eq!((editor.line(0), editor.line(1)), ("bc".into(), "a".into()));
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+        editor.move_line_up();
+        assert_eq!(editor.contents(), "bc\na\nd");
+
+        editor.move_line_down();
+        editor.move_line_down();
+        assert_eq!(editor.line(2), "bc");
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 2 });
+        editor.move_line_down();
+        assert_eq!(editor.contents(), "a\nd\nbc");
+    }
+
+    #[test]
+    fn delete_to_line_end_kills_the_tail() {
+        let mut editor = editor_at("abcd\nef", 0, 1);
+        editor.delete_to_line_end();
+        assert_eq!(editor.curr_ln(), "a");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 1 });
+
+        // An empty tail joins the next line.
+        editor.delete_to_line_end();
+        assert_eq!(editor.curr_ln(), "aef");
+        editor.move_to_line_end(false);
+        editor.delete_to_line_end();
+        assert_eq!(editor.contents(), "aef");
+    }
+
+    #[test]
+    fn delete_to_line_start_kills_the_head() {
+        let mut editor = editor_at("abcd\nef", 0, 3);
+        editor.delete_to_line_start();
+        assert_eq!(editor.curr_ln(), "d");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+        editor.delete_to_line_start();
+        assert_eq!(editor.contents(), "d\nef");
+    }
+
+    #[test]
+    fn yank_and_paste_the_register() {
+        let mut editor = Editor::from("hello world\n");
+        editor.set_selection(Cursor { ln: 0, col: 6 }, Cursor { ln: 0, col: 11 });
+        editor.yank();
+        assert_eq!(editor.register(), "world");
+
+        editor.selection.anchor = None;
+        editor.move_to_bottom();
+        editor.paste_register();
+        editor.paste_register();
+        assert_eq!(editor.contents(), "hello world\nworldworld");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 10 });
+
+        // Without a selection, the line is yanked.
+        editor.yank();
+        assert_eq!(editor.register(), "worldworld");
+    }
+
+    #[test]
+    fn trim_trailing_spaces_and_tabs() {
+        let mut editor = editor_at("a \t \n\t b\t\n  \nc", 0, 5);
+        editor.trim_trailing_whitespace();
+        assert_eq!(editor.contents(), "a\n\t b\n\nc");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 1 });
+    }
+
+    #[test]
+    fn insert_str_places_the_focus_after_the_text() {
+        let mut editor = editor_at("ab", 0, 1);
+        editor.insert_str("x\ny");
+        assert_eq!(editor.contents(), "ax\nyb");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 1 });
+
+        let mut editor = editor_at("ab", 0, 1);
+        editor.insert_str("x\n");
+        assert_eq!(editor.contents(), "ax\nb");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        let mut editor = editor_at("ab", 0, 1);
+        editor.insert_str("xyz");
+        assert_eq!(editor.contents(), "axyzb");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 4 });
+    }
+
+    #[test]
+    fn backspace_joins_with_the_previous_line() {
+        let mut editor = editor_at("ab\n\ncd", 2, 0);
+        editor.backspace();
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+
+        editor.backspace();
+        assert_eq!(editor.contents(), "abcd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+    }
+
+    #[test]
+    fn delete_char_before_the_buffer_start_does_nothing() {
+        let mut editor = Editor::from("ab");
+        editor.delete_char(-1);
+        assert_eq!(editor.contents(), "ab");
+        editor.insert_char(-1, 'x');
+        assert_eq!(editor.contents(), "ab");
+
+        // Offsets within the buffer still work.
+        editor.selection.focus.col = 1;
+        editor.delete_char(-1);
+        assert_eq!(editor.contents(), "b");
+        editor.delete_char(isize::MAX);
+        assert_eq!(editor.contents(), "b");
+    }
+
+    #[test]
+    fn move_to_bottom_of_an_empty_buffer() {
+        let mut editor = Editor::default();
+        assert_eq!(editor.last_line(), 0);
+        editor.move_to_bottom();
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+        editor.type_char('a');
+        assert_eq!(editor.contents(), "a");
+    }
+
+    #[test]
+    fn select_word_in_the_middle_of_a_line() {
+        let mut editor = editor_at("let foo_bar = 1;", 0, 6);
+        editor.select_word();
+        assert_eq!(editor.curr_sel().unwrap(), "foo_bar");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 11 });
+    }
+
+    #[test]
+    fn select_word_touching_the_line_end() {
+        let mut editor = editor_at("say hello\nnext", 0, 9);
+        editor.select_word();
+        assert_eq!(editor.curr_sel().unwrap(), "hello");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 4 }));
+    }
+
+    #[test]
+    fn select_line_includes_the_line_break() {
+        let mut editor = editor_at("ab\ncd", 0, 1);
+        editor.select_line();
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 0 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 0 });
+        editor.selection.focus = Cursor { ln: 1, col: 0 };
+        editor.select_line();
+        assert_eq!(editor.curr_sel().unwrap(), "cd");
+    }
+
+    #[test]
+    fn toggle_line_comment_after_the_indentation() {
+        let mut editor = editor_at("    let a = 1;", 0, 8);
+        editor.toggle_line_comment("//");
+        assert_eq!(editor.contents(), "    // let a = 1;");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 11 });
+        editor.toggle_line_comment("//");
+        assert_eq!(editor.contents(), "    let a = 1;");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 8 });
+    }
+
+    #[test]
+    fn toggle_line_comment_on_a_mixed_selection() {
+        let mut editor = editor_at("// a\n  b\n\n  // c", 3, 7);
+        editor.selection.anchor = Some(Cursor { ln: 0, col: 0 });
+        editor.toggle_line_comment("//");
+        assert_eq!(editor.contents(), "// // a\n  // b\n\n  // // c");
+        editor.toggle_line_comment("//");
+        assert_eq!(editor.contents(), "// a\n  b\n\n  // c");
+    }
+
+    #[test]
+    fn indent_and_dedent_a_three_line_selection() {
+        let mut editor = editor_at("ab\ncd\nef", 2, 1);
+        editor.selection.anchor = Some(Cursor { ln: 0, col: 1 });
+        editor.indent_selection("    ");
+        assert_eq!(editor.contents(), "    ab\n    cd\n    ef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 5 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 5 });
+        editor.dedent_selection();
+        assert_eq!(editor.contents(), "ab\ncd\nef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 1 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 1 });
+    }
+
+    #[test]
+    fn dedent_a_three_line_selection_with_uneven_indentation() {
+        let mut editor = editor_at("\tab\n  cd\nef", 0, 0);
+        editor.selection.anchor = Some(Cursor { ln: 2, col: 2 });
+        editor.dedent_selection();
+        assert_eq!(editor.contents(), "ab\ncd\nef");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 2, col: 2 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+    }
+
+    #[test]
+    fn state_round_trips_through_restore() {
+        let mut editor = editor_at("ab\ncd\n", 1, 1);
+        editor.selection.anchor = Some(Cursor { ln: 0, col: 1 });
+        let state = editor.state();
+        let mut restored = Editor::default();
+        restored.restore(&state);
+        assert_eq!(restored.state(), state);
+        assert_eq!(restored.line_count(), 3);
+        assert_eq!(restored.curr_sel().unwrap(), "b\nc");
+    }
+
+    #[test]
+    fn crlf_state_is_restored_without_carriage_returns() {
+        let mut editor = Editor::from("ab\r\ncd");
+        editor.selection.focus = Cursor { ln: 0, col: 2 };
+        let state = editor.state();
+        assert_eq!(state.contents, "ab\r\ncd");
+        let mut restored = Editor::default();
+        restored.restore(&state);
+        assert_eq!(restored.line(0), "ab");
+        assert_eq!(restored.contents(), "ab\r\ncd");
+        assert_eq!(restored.state(), state);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_round_trips_through_serde() {
+        let mut editor = editor_at("ab\ncd", 1, 2);
+        editor.selection.anchor = Some(Cursor { ln: 0, col: 0 });
+        let json = serde_json::to_string(&editor.state()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"contents":"ab\ncd","selection":{"focus":{"ln":1,"col":2},"anchor":{"ln":0,"col":0},"block":false}}"#
+        );
+        let state: EditorState = serde_json::from_str(&json).unwrap();
+        let mut restored = Editor::default();
+        restored.restore(&state);
+        assert_eq!(restored.contents(), "ab\ncd");
+        assert_eq!(restored.selection, editor.selection);
+    }
+
+    #[test]
+    fn from_str_places_the_focus_at_the_start() {
+        let editor: Editor = "ab\ncd".parse().unwrap();
+        assert_eq!(editor.contents(), "ab\ncd");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+        assert_eq!(editor.selection.anchor, None);
+
+        let editor = Editor::default().with_contents("x\r\ny");
+        assert_eq!(editor.line_count(), 2);
+        assert_eq!(editor.newline(), Newline::CrLf);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 0 });
+    }
+
+    #[test]
+    fn drive_runs_a_script_of_events() {
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let events = vec![
+            key(KeyCode::Char('a')),
+            key(KeyCode::Enter),
+            key(KeyCode::Char('b')),
+            Event::Key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            key(KeyCode::Char('c')),
+        ];
+        let mut out = Vec::new();
+        let mut editor = Editor::default();
+        let signal = editor
+            .drive(
+                NormalKeybinding::default(),
+                StringRenderer::render_to(&mut out),
+                events,
+            )
+            .unwrap();
+        assert_eq!(signal, Signal::Submit);
+        assert_eq!(editor.contents(), "a\nb");
+        assert_eq!(String::from_utf8(out).unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn drive_stops_when_the_events_run_out() {
+        let mut out = Vec::new();
+        let mut editor = Editor::default();
+        let events = vec![Event::Paste("ab".to_string())];
+        let signal = editor
+            .drive(
+                NormalKeybinding::default(),
+                StringRenderer::render_to(&mut out),
+                events,
+            )
+            .unwrap();
+        assert_eq!(signal, Signal::Continue);
+        assert_eq!(editor.contents(), "ab");
+    }
+
+    #[test]
+    fn backspace_removes_a_whole_flag() {
+        let flag = "\u{1F1EF}\u{1F1F5}";
+        let mut editor = Editor::from(format!("a{}b", flag).as_str());
+        editor.move_right(false);
+        editor.move_right(false);
+        assert_eq!(editor.selection.focus.col, 3);
+        editor.backspace();
+        assert_eq!(editor.contents(), "ab");
+        assert_eq!(editor.selection.focus.col, 1);
+    }
+
+    #[test]
+    fn insert_from_reader_in_the_middle_of_text() {
+        let mut editor = editor_at("abcd\nef", 0, 2);
+        editor.insert_from_reader("12\r\n34\n5".as_bytes()).unwrap();
+        assert_eq!(editor.contents(), "ab12\n34\n5cd\nef");
+        assert_eq!(editor.selection.focus, Cursor { ln: 2, col: 1 });
+        editor.undo();
+        assert_eq!(editor.contents(), "abcd\nef");
+    }
+
+    #[test]
+    fn append_contents_starts_a_new_line_and_notifies() {
+        let count = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = std::rc::Rc::clone(&count);
+        let mut editor = Editor::from("ab")
+            .with_max_lines(Some(1))
+            .with_on_change(move |_| counter.set(counter.get() + 1));
+        editor.selection.focus = Cursor { ln: 0, col: 1 };
+        editor.append_contents("cd\r\nef".as_bytes()).unwrap();
+        editor.end_event();
+        assert_eq!(editor.contents(), "ab\ncd\nef");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 1 });
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn crlf_contents_round_trip() {
+        let text = "ab\r\ncd\r\n\r\nef";
+        let mut editor = Editor::from(text);
+        assert_eq!(editor.newline(), Newline::CrLf);
+        assert_eq!(editor.line_count(), 4);
+        assert_eq!(editor.line(0), "ab");
+        assert_eq!(editor.contents(), text);
+
+        editor.selection.focus = Cursor { ln: 0, col: 2 };
+        editor.type_char('\n');
+        assert_eq!(editor.contents(), "ab\r\n\r\ncd\r\n\r\nef");
+
+        editor.set_contents("x\ny".as_bytes()).unwrap();
+        assert_eq!(editor.contents(), "x\ny");
+    }
+
+    #[test]
+    fn newline_style_can_be_set() {
+        let editor = Editor::from("a\nb").with_newline(Newline::CrLf);
+        assert_eq!(editor.contents(), "a\r\nb");
+    }
+
+    #[test]
+    fn trailing_newline_ends_the_contents_with_one_break() {
+        let editor = Editor::from("ab").with_trailing_newline(true);
+        assert_eq!(editor.contents(), "ab\n");
+        let editor = Editor::from("ab\n").with_trailing_newline(true);
+        assert_eq!(editor.contents(), "ab\n");
+        let editor = Editor::from("ab\r\n").with_trailing_newline(true);
+        assert_eq!(editor.contents(), "ab\r\n");
+        let editor = Editor::from("ab\n\n").with_trailing_newline(true);
+        assert_eq!(editor.contents(), "ab\n\n");
+        // An empty buffer stays empty.
+        let editor = Editor::default().with_trailing_newline(true);
+        assert_eq!(editor.contents(), "");
+    }
+
+    #[test]
+    fn trailing_newline_is_left_out_by_default() {
+        assert_eq!(Editor::from("ab").contents(), "ab");
+        assert_eq!(Editor::from("ab\n").contents(), "ab");
+        assert_eq!(Editor::from("ab\n\n").contents(), "ab\n");
+    }
+
+    #[test]
+    fn transpose_at_the_line_end_swaps_the_last_two() {
+        let mut editor = editor_at("ab", 0, 2);
+        editor.transpose_chars();
+        assert_eq!(editor.contents(), "ba");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+    }
+
+    #[test]
+    fn transpose_in_the_middle_swaps_around_the_focus() {
+        let mut editor = editor_at("ab", 0, 1);
+        editor.transpose_chars();
+        assert_eq!(editor.contents(), "ba");
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 2 });
+    }
+
+    #[test]
+    fn transpose_at_the_line_start_or_on_one_char_does_nothing() {
+        let mut editor = editor_at("ab\nc", 0, 0);
+        editor.transpose_chars();
+        editor.selection.focus = Cursor { ln: 1, col: 1 };
+        editor.transpose_chars();
+        assert_eq!(editor.contents(), "ab\nc");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 1 });
+
+        let mut editor = editor_at("e\u{301}", 0, 2);
+        editor.transpose_chars();
+        assert!(!editor.changed);
+        editor.undo();
+        assert!(!editor.changed);
+        assert_eq!(editor.contents(), "e\u{301}");
+    }
+
+    #[test]
+    fn uppercase_selection_grows_for_multi_char_mappings() {
+        let mut editor = editor_at("straße x", 0, 6);
+        editor.selection.anchor = Some(Cursor { ln: 0, col: 0 });
+        editor.uppercase_selection();
+        assert_eq!(editor.contents(), "STRASSE x");
+        assert_eq!(editor.selection.anchor, Some(Cursor { ln: 0, col: 0 }));
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 7 });
+        assert_eq!(editor.curr_sel().unwrap(), "STRASSE");
+    }
+
+    #[test]
+    fn case_transforms_apply_to_the_word_without_a_selection() {
+        let mut editor = editor_at("groß Wort", 0, 2);
+        editor.uppercase_selection();
+        assert_eq!(editor.contents(), "GROSS Wort");
+        assert_eq!(editor.selection.anchor, None);
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 5 });
+
+        editor.selection.focus = Cursor { ln: 0, col: 7 };
+        editor.toggle_case_selection();
+        assert_eq!(editor.contents(), "GROSS wORT");
+        editor.lowercase_selection();
+        assert_eq!(editor.contents(), "GROSS wort");
+    }
+
+    /// Create an editor with smart backspace and the focus at a column.
+    fn smart(contents: &str, col: usize) -> Editor {
+        let mut editor = Editor::from(contents)
+            .with_tab_width(4)
+            .with_smart_backspace(true);
+        editor.selection.focus = Cursor { ln: 0, col };
+        editor
+    }
+
+    #[test]
+    fn smart_backspace_at_column_4_removes_a_level() {
+        let mut editor = smart("        x", 4);
+        editor.backspace();
+        assert_eq!(editor.contents(), "    x");
+        assert_eq!(editor.selection.focus.col, 0);
+    }
+
+    #[test]
+    fn smart_backspace_at_column_6_goes_back_to_the_tab_stop() {
+        let mut editor = smart("      x", 6);
+        editor.backspace();
+        assert_eq!(editor.contents(), "    x");
+        assert_eq!(editor.selection.focus.col, 4);
+    }
+
+    #[test]
+    fn smart_backspace_only_applies_within_the_indentation() {
+        let mut editor = smart("ab  x", 4);
+        editor.backspace();
+        assert_eq!(editor.contents(), "ab x");
+
+        let mut editor = smart("    x", 4).with_smart_backspace(false);
+        editor.backspace();
+        assert_eq!(editor.contents(), "   x");
+    }
+
+    #[test]
+    fn content_is_trimmed_before_it_is_validated() {
+        let no_trailing_space = |contents: &str| {
+            if contents.ends_with(' ') {
+                Err("trailing space".to_string())
+            } else {
+                Ok(())
+            }
+        };
+        let mut editor = Editor::default()
+            .with_trim_on_submit(true)
+            .with_validator(no_trailing_space);
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let events = vec![
+            key(KeyCode::Char('a')),
+            key(KeyCode::Char(' ')),
+            Event::Key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+        ];
+        let mut out = Vec::new();
+        let signal = editor
+            .drive(
+                NormalKeybinding::default(),
+                StringRenderer::render_to(&mut out),
+                events,
+            )
+            .unwrap();
+        assert_eq!(signal, Signal::Submit);
+        assert_eq!(editor.validation_error(), None);
+        assert_eq!(editor.contents(), "a");
     }
 }
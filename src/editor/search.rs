@@ -0,0 +1,328 @@
+use std::{collections::VecDeque, ops::Range, slice};
+
+use super::{selection::Cursor, Editor};
+
+impl Editor {
+    /// Find the positions of all occurrences of a string. The search
+    /// ignores case unless the string contains an uppercase character.
+    pub fn search(&self, needle: &str) -> Vec<Cursor> {
+        if smart_case(needle) {
+            self.find_all_ignore_case(needle)
+        } else {
+            self.find_all(needle)
+        }
+    }
+
+    /// Select the next occurrence of a string after the focus,
+    /// wrapping around to the start of the buffer.
+    /// Returns `false` if there are no occurrences.
+    pub fn find_next(&mut self, needle: &str) -> bool {
+        let matches = self.find_ranges(needle, smart_case(needle));
+        let focus = self.rope_idx(self.selection.focus);
+        let next = matches
+            .iter()
+            .find(|range| range.start >= focus)
+            .or_else(|| matches.first());
+        match next {
+            Some(range) => {
+                self.select_match(range.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select the previous occurrence of a string before the selection,
+    /// wrapping around to the end of the buffer.
+    /// Returns `false` if there are no occurrences.
+    pub fn find_prev(&mut self, needle: &str) -> bool {
+        let matches = self.find_ranges(needle, smart_case(needle));
+        let start = self
+            .selection
+            .anchor
+            .map_or(self.selection.focus, |anchor| {
+                anchor.min(self.selection.focus)
+            });
+        let start = self.rope_idx(start);
+        let prev = matches
+            .iter()
+            .rev()
+            .find(|range| range.start < start)
+            .or_else(|| matches.last());
+        match prev {
+            Some(range) => {
+                self.select_match(range.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace all occurrences of a string, returning the number of
+    /// replacements. Case is matched the same way as in `search`.
+    /// Nothing is replaced if the result would not fit within
+    /// the maximum number of characters or lines.
+    pub fn replace_all(&mut self, needle: &str, replacement: &str) -> usize {
+        if self.read_only() {
+            return 0;
+        }
+        let matches = self.find_ranges(needle, smart_case(needle));
+        if !self.fits_replacement(&matches, replacement) {
+            return 0;
+        }
+        let replacement_len = replacement.chars().count();
+
+        let mut focus = self.rope_idx(self.selection.focus);
+        for range in matches.iter().rev() {
+            let (idx, len) = (range.start, range.len());
+            self.rope_remove(range.clone());
+            self.rope_insert(idx, replacement);
+            if focus >= idx + len {
+                focus = focus - len + replacement_len;
+            } else if focus > idx {
+                focus = idx + replacement_len;
+            }
+        }
+
+        self.selection.anchor = None;
+        self.selection.focus = self.cursor_at(focus.min(self.buf.len_chars()));
+        matches.len()
+    }
+
+    /// Replace the first occurrence of a string at or after the start
+    /// of the selection, leaving the focus after the replacement.
+    /// Returns `false` if there is no such occurrence, or if the
+    /// replacement would not fit within the maximum number of
+    /// characters or lines.
+    pub fn replace_next(&mut self, needle: &str, replacement: &str) -> bool {
+        if self.read_only() {
+            return false;
+        }
+        let start = self
+            .selection
+            .anchor
+            .map_or(self.selection.focus, |anchor| {
+                anchor.min(self.selection.focus)
+            });
+        let start = self.rope_idx(start);
+        let matches = self.find_ranges(needle, smart_case(needle));
+        match matches.into_iter().find(|range| range.start >= start) {
+            Some(range) if self.fits_replacement(slice::from_ref(&range), replacement) => {
+                let idx = range.start;
+                self.rope_remove(range);
+                self.rope_insert(idx, replacement);
+                self.selection.anchor = None;
+                self.selection.focus = self.cursor_at(idx + replacement.chars().count());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if replacing the ranges keeps the buffer within the
+    /// maximum number of characters and lines.
+    fn fits_replacement(&self, ranges: &[Range<usize>], replacement: &str) -> bool {
+        let removed_chars: usize = ranges.iter().map(Range::len).sum();
+        let removed_lines: usize = ranges
+            .iter()
+            .map(|range| self.buf.slice(range.clone()).len_lines() - 1)
+            .sum();
+        let added_chars = replacement.chars().count() * ranges.len();
+        let added_lines = replacement.matches('\n').count() * ranges.len();
+
+        let chars = added_chars.saturating_sub(removed_chars);
+        let lines = added_lines.saturating_sub(removed_lines);
+        (chars == 0 || self.fits_chars(chars)) && (lines == 0 || self.fits_lines(lines))
+    }
+
+    fn select_match(&mut self, range: Range<usize>) {
+        self.selection.anchor = Some(self.cursor_at(range.start));
+        self.selection.focus = self.cursor_at(range.end);
+    }
+
+    /// Find the positions of all non-overlapping occurrences of a string.
+    pub fn find_all(&self, needle: &str) -> Vec<Cursor> {
+        self.find_ranges(needle, false)
+            .into_iter()
+            .map(|range| self.cursor_at(range.start))
+            .collect()
+    }
+
+    /// Find the positions of all non-overlapping occurrences
+    /// of a string, ignoring case.
+    pub fn find_all_ignore_case(&self, needle: &str) -> Vec<Cursor> {
+        self.find_ranges(needle, true)
+            .into_iter()
+            .map(|range| self.cursor_at(range.start))
+            .collect()
+    }
+
+    /// Find the character ranges of all non-overlapping occurrences of
+    /// a string in a single pass over the buffer.
+    ///
+    /// Ignoring case compares the full lowercase forms, so a match can
+    /// have a different length than the string, but it always covers
+    /// whole characters of the buffer.
+    pub(crate) fn find_ranges(&self, needle: &str, ignore_case: bool) -> Vec<Range<usize>> {
+        let fold = |c: char| {
+            let lower = Some(c.to_lowercase()).filter(|_| ignore_case);
+            let same = Some(c).filter(|_| !ignore_case);
+            lower.into_iter().flatten().chain(same)
+        };
+
+        let needle: Vec<char> = needle.chars().flat_map(fold).collect();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        // Knuth-Morris-Pratt failure table.
+        let mut fail = vec![0; needle.len()];
+        let mut k = 0;
+        for i in 1..needle.len() {
+            while k > 0 && needle[i] != needle[k] {
+                k = fail[k - 1];
+            }
+            if needle[i] == needle[k] {
+                k += 1;
+            }
+            fail[i] = k;
+        }
+
+        // The buffer index of the last folded characters, and whether
+        // each is the first of the folded form of its character.
+        let mut recent = VecDeque::with_capacity(needle.len());
+        let mut matches = Vec::new();
+        let mut k = 0;
+        for (idx, c) in self.buf.chars().enumerate() {
+            let mut folded = fold(c).peekable();
+            let mut first = true;
+            while let Some(c) = folded.next() {
+                if recent.len() == needle.len() {
+                    recent.pop_front();
+                }
+                recent.push_back((idx, first));
+                first = false;
+                while k > 0 && c != needle[k] {
+                    k = fail[k - 1];
+                }
+                if c == needle[k] {
+                    k += 1;
+                }
+                if k == needle.len() {
+                    // Only matches of whole characters count.
+                    let (start, starts_char) = recent[recent.len() - k];
+                    if starts_char && folded.peek().is_none() {
+                        matches.push(start..idx + 1);
+                        // Restart so that matches do not overlap.
+                        k = 0;
+                    } else {
+                        k = fail[k - 1];
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
+/// Check if a search should ignore case, which is
+/// when the needle has no uppercase characters.
+fn smart_case(needle: &str) -> bool {
+    !needle.chars().any(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_all_does_not_overlap() {
+        let editor = Editor::from("aaaaa\nababab");
+        let cursors = editor.find_all("aa");
+        assert_eq!(
+            cursors,
+            vec![Cursor { ln: 0, col: 0 }, Cursor { ln: 0, col: 2 }]
+        );
+        let cursors = editor.find_all("abab");
+        assert_eq!(cursors, vec![Cursor { ln: 1, col: 0 }]);
+        assert!(editor.find_all("").is_empty());
+    }
+
+    #[test]
+    fn find_all_with_multibyte_needles() {
+        let editor = Editor::from("café ☕ CAFÉ\n日本語の日本");
+        let cursors = editor.find_all("日本");
+        assert_eq!(
+            cursors,
+            vec![Cursor { ln: 1, col: 0 }, Cursor { ln: 1, col: 4 }]
+        );
+        let cursors = editor.find_all_ignore_case("é");
+        assert_eq!(
+            cursors,
+            vec![Cursor { ln: 0, col: 3 }, Cursor { ln: 0, col: 10 }]
+        );
+        assert_eq!(editor.find_all("☕"), vec![Cursor { ln: 0, col: 5 }]);
+    }
+
+    #[test]
+    fn ignoring_case_matches_whole_characters() {
+        // `İ` lowercases to two characters, `i` and a combining dot.
+        let editor = Editor::from("İi");
+        assert_eq!(editor.find_ranges("i\u{307}", true), vec![0..1]);
+        assert_eq!(editor.find_ranges("i", true), vec![1..2]);
+    }
+
+    #[test]
+    fn replace_all_with_a_longer_folded_match() {
+        let mut editor = Editor::from("İstanbul, istanbul");
+        assert_eq!(editor.replace_all("i\u{307}stanbul", "x"), 1);
+        assert_eq!(editor.contents(), "x, istanbul");
+    }
+
+    #[test]
+    fn replace_all_across_lines() {
+        let mut editor = Editor::from("one two\ntwo\nthree two");
+        editor.selection.focus = Cursor { ln: 2, col: 9 };
+        assert_eq!(editor.replace_all("two", "2\n"), 3);
+        assert_eq!(editor.line_count(), 6);
+        assert_eq!(editor.contents(), "one 2\n\n2\n\nthree 2");
+        // The focus stays after the replacement it was at.
+        assert_eq!(editor.selection.focus, Cursor { ln: 5, col: 0 });
+
+        assert_eq!(editor.replace_all("2\n\n", ""), 2);
+        assert_eq!(editor.contents(), "one three 2");
+        assert_eq!(editor.replace_all("missing", "x"), 0);
+    }
+
+    #[test]
+    fn replace_next_after_the_focus() {
+        let mut editor = Editor::from("ab\nab\nab");
+        editor.selection.focus = Cursor { ln: 0, col: 1 };
+        assert!(editor.replace_next("ab", "xyz"));
+        assert_eq!(editor.contents(), "ab\nxyz\nab");
+        assert_eq!(editor.selection.focus, Cursor { ln: 1, col: 3 });
+        assert!(editor.replace_next("ab", "xyz"));
+        assert_eq!(editor.contents(), "ab\nxyz\nxyz");
+        assert!(!editor.replace_next("ab", "xyz"));
+    }
+
+    #[test]
+    fn replacements_stay_within_the_limits() {
+        let mut editor = Editor::from("ab ab").with_max_chars(Some(7));
+        assert_eq!(editor.replace_all("ab", "abcd"), 0);
+        assert_eq!(editor.contents(), "ab ab");
+        assert!(editor.replace_next("ab", "abcd"));
+        assert!(!editor.replace_next("ab", "abcd"));
+        assert_eq!(editor.contents(), "abcd ab");
+        // Shrinking is always allowed.
+        assert_eq!(editor.replace_all("b", ""), 2);
+        assert_eq!(editor.contents(), "acd a");
+
+        let mut editor = Editor::from("a a\nb").with_max_lines(Some(3));
+        assert_eq!(editor.replace_all("a", "\n"), 0);
+        assert_eq!(editor.contents(), "a a\nb");
+        assert!(editor.replace_next("a", "\n"));
+        assert_eq!(editor.line_count(), 3);
+        assert!(!editor.replace_next("a", "\n"));
+    }
+}
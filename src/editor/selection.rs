@@ -1,13 +1,18 @@
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cursor {
     pub ln: usize,
     pub col: usize,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Selection {
     pub focus: Cursor,
-    pub anchor: Option<Cursor>
+    pub anchor: Option<Cursor>,
+    /// Whether the selection is a rectangle between the anchor and the
+    /// focus, covering the same columns on every line.
+    pub block: bool,
 }
 
 impl Selection {
@@ -15,17 +20,19 @@ impl Selection {
     pub fn fix_anchor(&mut self) {
         if self.anchor == Some(self.focus) {
             self.anchor = None;
+            self.block = false;
         }
     }
 
     /// Anchor if there was not already an anchor, or unanchor.
     pub fn set_anchor(&mut self, anchored: bool) {
         if anchored {
-            if self.anchor == None {
+            if self.anchor.is_none() {
                 self.anchor = Some(self.focus);
             }
         } else {
-            self.anchor = None
+            self.anchor = None;
+            self.block = false;
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+/// Check of the content before it is submitted.
+pub trait Validator {
+    /// Check the content, returning a message explaining why
+    /// it cannot be submitted if it is invalid.
+    fn validate(&self, contents: &str) -> Result<(), String>;
+}
+
+impl<F> Validator for F
+where
+    F: Fn(&str) -> Result<(), String>,
+{
+    fn validate(&self, contents: &str) -> Result<(), String> {
+        self(contents)
+    }
+}
@@ -4,8 +4,10 @@ use thiserror::Error;
 pub enum Error {
     #[error("IO error")]
     Io(#[from] std::io::Error),
+    /// Failure of the terminal, such as to enable raw mode or read
+    /// an event. Crossterm reports these as IO errors.
     #[error("Terminal error: {0}")]
-    Terminal(#[from] crossterm::ErrorKind),
+    Terminal(std::io::Error),
     #[allow(dead_code)]
     #[error("Unknown error")]
     Unknown,
@@ -15,7 +15,7 @@ use minime::{
     Result,
 };
 
-const CARGO_PKG_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() -> Result<()> {
     let matches = App::new("Mini-Me")
@@ -49,6 +49,11 @@ fn main() -> Result<()> {
                 .short("ne")
                 .conflicts_with("OUTPUT")
         )
+        .arg(
+            Arg::with_name("NOFINALNEWLINE")
+                .help("Do not end the editor content with a line break")
+                .long("no-final-newline"),
+        )
         .arg(
             Arg::with_name("OUTPUT")
                 .short("o")
@@ -63,6 +68,7 @@ fn main() -> Result<()> {
     let file_path = matches.value_of("FILE");
     let no_save = matches.is_present("NOSAVE");
     let no_emit = matches.is_present("NOEMIT");
+    let final_newline = !matches.is_present("NOFINALNEWLINE");
 
     let output_path = if no_save {
         None
@@ -75,12 +81,12 @@ fn main() -> Result<()> {
     let stderr = std::io::stderr();
     let mut lock = BufWriter::new(stderr.lock());
 
-    let renderer = CrosstermRenderer::render_to(&mut lock)
+    let renderer = CrosstermRenderer::try_render_to(&mut lock)?
         .max_height(max_height)
-        .margin(FancyGutter)
+        .margin(FancyGutter::default())
         .footer(FancyFooter);
 
-    let mut term = Editor::default();
+    let mut term = Editor::default().with_trailing_newline(final_newline);
 
     if let Some(file) = file {
         term.set_contents(BufReader::new(file))?;
@@ -88,9 +94,11 @@ fn main() -> Result<()> {
         term.move_to_line_end(false);
     }
 
-    term.read(NormalKeybinding, renderer)?;
-
-    let contents = term.contents();
+    let contents = match term.read(NormalKeybinding::default(), renderer)? {
+        Some(contents) => contents,
+        // Cancelled, leave the file untouched.
+        None => return Ok(()),
+    };
 
     if let Some(file) = output_path.and_then(|path| {
         OpenOptions::new()
@@ -106,7 +114,6 @@ fn main() -> Result<()> {
         let stdout = std::io::stdout();
         let mut writer = stdout.lock();
         writer.write_all(contents.as_bytes())?;
-        writer.write(b"\n")?;
     }
 
     Ok(())
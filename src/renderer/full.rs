@@ -1,16 +1,23 @@
 use std::{
     convert::TryInto,
-    io::{stdout, Stdout, Write},
+    io::{self, stdout, Stdout, Write},
+    ops::{Deref, DerefMut},
 };
 
 use super::{
-    styles::{Footer, Header, Margin, NoStyle},
-    Editor, Renderer,
+    styles::{Footer, Header, Highlighter, InlineSuffix, Margin, NoStyle, StyleBundle},
+    Editor, LineStyle, Renderer,
+};
+use crate::{
+    editor::selection::Cursor,
+    util::{base64, char_width},
+    Result,
 };
-use crate::{editor::selection::Cursor, Result};
 
 use crossterm::{
     cursor::*,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor, Stylize},
     terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
     QueueableCommand,
 };
@@ -19,32 +26,160 @@ use raw_mode::RawModeGuard;
 mod raw_mode {
     use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
-    use super::Result;
+    use crate::{error::Error, Result};
 
-    pub struct RawModeGuard(());
+    /// Keeps raw mode enabled while alive, if it was enabled.
+    pub struct RawModeGuard(bool);
 
     impl RawModeGuard {
         pub fn acquire() -> Result<RawModeGuard> {
-            enable_raw_mode()?;
-            Ok(Self(()))
+            enable_raw_mode().map_err(Error::Terminal)?;
+            Ok(Self(true))
+        }
+
+        /// Create a guard that leaves the terminal alone.
+        #[cfg(test)]
+        pub fn disabled() -> RawModeGuard {
+            Self(false)
         }
     }
 
     impl Drop for RawModeGuard {
         fn drop(&mut self) {
-            disable_raw_mode().unwrap();
+            if self.0 {
+                // Nothing can be done about a failure while dropping.
+                let _ = disable_raw_mode();
+            }
+        }
+    }
+}
+
+/// Background of the line being edited, when highlighted.
+const CURRENT_LINE: Color = Color::AnsiValue(236);
+
+/// Writer of a renderer, either borrowed or owned.
+enum Writer<'w, W> {
+    Borrowed(&'w mut W),
+    Owned(W),
+}
+
+/// Output of a renderer, which restores the terminal modes if it is
+/// dropped after drawing without finishing, such as during a panic.
+struct Output<'w, W> {
+    write: Writer<'w, W>,
+    restore: fn(&mut W),
+    dirty: bool,
+}
+
+impl<'w, W: Write> Output<'w, W> {
+    fn new(write: Writer<'w, W>) -> Self {
+        Self {
+            write,
+            restore: restore_terminal,
+            dirty: false,
         }
     }
 }
 
+impl<W> Deref for Output<'_, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        match &self.write {
+            Writer::Borrowed(write) => write,
+            Writer::Owned(write) => write,
+        }
+    }
+}
+
+impl<W> DerefMut for Output<'_, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        match &mut self.write {
+            Writer::Borrowed(write) => write,
+            Writer::Owned(write) => write,
+        }
+    }
+}
+
+impl<W> Drop for Output<'_, W> {
+    fn drop(&mut self) {
+        if self.dirty {
+            (self.restore)(self);
+        }
+    }
+}
+
+/// Leave every mode the renderer may have entered.
+fn restore_terminal<W: Write>(write: &mut W) {
+    // Nothing can be done about a failure while dropping.
+    let _ = write
+        .queue(LeaveAlternateScreen)
+        .and_then(|w| w.queue(SetCursorStyle::DefaultUserShape))
+        .and_then(|w| w.queue(Show))
+        .and_then(|w| w.queue(DisableMouseCapture))
+        .and_then(|w| w.queue(DisableBracketedPaste))
+        .and_then(|w| w.flush());
+}
+
 pub struct CrosstermRenderer<'b, W, M, H, F> {
     guard: RawModeGuard,
-    write: &'b mut W,
+    write: Output<'b, W>,
     margin: M,
     header: H,
     footer: F,
     draw_state: DrawState,
     max_height: Option<usize>,
+    anchor: Anchor,
+    overflow: Overflow,
+    suffix: Option<Box<dyn InlineSuffix + 'b>>,
+    suffix_on_focus: bool,
+    highlighter: Option<Box<dyn Highlighter + 'b>>,
+    scrollbar: bool,
+    wrap: bool,
+    highlight_current_line: bool,
+    render_whitespace: bool,
+    escape_control: bool,
+    cursor_style: Option<SetCursorStyle>,
+    capture_mouse: bool,
+    line_markers: bool,
+    osc52_clipboard: bool,
+    scroll_off: usize,
+    /// Columns and rows of the terminal, if fixed instead of queried.
+    size: Option<(u16, u16)>,
+}
+
+/// Where the frame is anchored when not in alternate screen mode.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Draw the frame in-place, growing downwards from where it started.
+    #[default]
+    InPlace,
+    /// Pad the top of the frame so its content stays pinned to
+    /// the bottom of the terminal.
+    Bottom,
+}
+
+/// How content taller than the frame is shown when not in
+/// alternate screen mode.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Scroll the lines to keep the focus in view.
+    #[default]
+    Scroll,
+    /// Show the first lines, with the lines below collapsed into
+    /// a `… (N more lines)` row, such as for previews. Moving the
+    /// focus into the collapsed lines scrolls instead. Lines are
+    /// always scrolled when wrapping.
+    Collapse,
+}
+
+/// Position of the editor content within the last frame drawn.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameAnchor {
+    /// Rows between the base of the frame and the first line of content.
+    pub base_row_offset: usize,
+    /// Column where the content starts, after the gutter.
+    pub gutter_col: usize,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,6 +194,14 @@ struct DrawState {
     high: usize,
     // Box cursor relative to the anchor
     cursor: Cursor,
+    // Columns of the terminal
+    cols: usize,
+    // Rows available for the content
+    rows: usize,
+    // Whether the cursor is hidden because the focus is scrolled out of view
+    cursor_hidden: bool,
+    mouse_captured: bool,
+    bracketed_paste: bool,
 }
 
 impl<W, M, H, F> Renderer for CrosstermRenderer<'_, W, M, H, F>
@@ -76,28 +219,67 @@ where
             self.move_to_frame_base()?;
         }
 
-        let (low, high, term_rows) = self.calculate_draw_range(&data);
+        let (low, high, term_rows) = self.calculate_draw_range(data);
 
         if term_rows == 0 {
             return Ok(());
         }
 
+        self.write.dirty = true;
         if data.altscreen && !self.draw_state.altscreen {
+            // The alternate screen keeps the cursor where the inline frame
+            // started, which is restored when leaving it.
             self.write.queue(EnterAlternateScreen)?;
+            self.write.queue(MoveTo(0, 0))?;
         } else if !data.altscreen && self.draw_state.altscreen {
             self.write.queue(LeaveAlternateScreen)?;
         }
 
-        self.draw_state = DrawState::default();
-        self.draw_state.altscreen = data.altscreen;
+        if self.capture_mouse && !self.draw_state.mouse_captured {
+            self.write.queue(EnableMouseCapture)?;
+        }
+        // Pastes arrive as a single event instead of typed keys.
+        if !self.draw_state.bracketed_paste {
+            self.write.queue(EnableBracketedPaste)?;
+        }
+        if let Some(text) = data.copied().filter(|_| self.osc52_clipboard) {
+            write!(self.write, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+        }
 
-        self.draw_header(&data)?;
-        self.draw_range(&data, low, high, term_rows)?;
-        self.draw_footer(&data)?;
+        self.draw_state = DrawState {
+            altscreen: data.altscreen,
+            cursor_hidden: self.draw_state.cursor_hidden,
+            mouse_captured: self.capture_mouse,
+            bracketed_paste: true,
+            ..DrawState::default()
+        };
+        self.draw_state.cols = self
+            .terminal_size()
+            .map(|(cols, _)| cols.into())
+            .unwrap_or(usize::MAX);
+        self.draw_state.rows = term_rows;
+
+        let collapsed = self.collapses(data, term_rows);
+        if !data.altscreen && self.anchor == Anchor::Bottom {
+            let rows = self.visual_rows(data, low, high) + collapsed as usize;
+            self.draw_padding(term_rows.saturating_sub(rows))?;
+        }
+        self.draw_header(data)?;
+        self.draw_range(data, low, high, term_rows)?;
+        if collapsed {
+            self.draw_collapsed(data, high)?;
+        }
+        data.set_viewport(low, high);
+        self.draw_footer(data)?;
         self.write.queue(Clear(ClearType::FromCursorDown))?;
 
-        self.draw_cursor(&data)?;
-        self.flush()
+        self.draw_cursor(data)?;
+        if let Some(cursor_style) = self.cursor_style {
+            self.write.queue(cursor_style)?;
+        }
+        self.flush()?;
+        data.set_scrollbar(self.scrollbar_track(data));
+        Ok(())
     }
 
     /// Clear the drawn prompt on the screen.
@@ -120,27 +302,124 @@ where
         Ok(())
     }
 
+    /// Clear the frame drawn for the old size, keeping the modes that
+    /// were entered, since the rows and wrapping of the frame change.
+    fn resize(&mut self, _: u16, _: u16) -> Result<()> {
+        let state = self.draw_state;
+        self.clear_draw()?;
+        self.draw_state = DrawState {
+            altscreen: state.altscreen,
+            cursor_hidden: state.cursor_hidden,
+            mouse_captured: state.mouse_captured,
+            bracketed_paste: state.bracketed_paste,
+            ..DrawState::default()
+        };
+        Ok(())
+    }
+
     fn finish(mut self) -> Result<()> {
+        let DrawState {
+            cursor_hidden,
+            mouse_captured,
+            bracketed_paste,
+            ..
+        } = self.draw_state;
         self.clear_draw()?;
 
         // if self.draw_state.altscreen {
         self.write.queue(LeaveAlternateScreen)?;
         // }
 
-        self.flush()
+        if self.cursor_style.is_some() {
+            self.write.queue(SetCursorStyle::DefaultUserShape)?;
+        }
+        if cursor_hidden {
+            self.write.queue(Show)?;
+        }
+        if mouse_captured {
+            self.write.queue(DisableMouseCapture)?;
+        }
+        if bracketed_paste {
+            self.write.queue(DisableBracketedPaste)?;
+        }
+
+        self.flush()?;
+        self.write.dirty = false;
+        Ok(())
     }
 }
 
-impl<'w, W> DefaultRenderer<'w, W> {
+impl<'w, W: Write> DefaultRenderer<'w, W> {
+    /// Create a renderer that writes to a writer, enabling raw mode.
+    ///
+    /// # Panics
+    /// Panics if raw mode cannot be enabled, such as when the terminal
+    /// is not a TTY. Use `try_render_to` to handle the error instead.
     pub fn render_to(write: &'w mut W) -> Self {
+        Self::try_render_to(write).expect("failed to enable raw mode")
+    }
+
+    /// Create a renderer that writes to a writer, enabling raw mode,
+    /// or return an error if raw mode cannot be enabled.
+    pub fn try_render_to(write: &'w mut W) -> Result<Self> {
+        Ok(Self::with_output(
+            Writer::Borrowed(write),
+            RawModeGuard::acquire()?,
+        ))
+    }
+
+    /// Create a renderer that owns its writer, enabling raw mode.
+    ///
+    /// # Panics
+    /// Panics if raw mode cannot be enabled.
+    pub fn render_owned(write: W) -> Self {
+        Self::try_render_owned(write).expect("failed to enable raw mode")
+    }
+
+    /// Create a renderer that owns its writer, enabling raw mode,
+    /// or return an error if raw mode cannot be enabled.
+    pub fn try_render_owned(write: W) -> Result<Self> {
+        Ok(Self::with_output(
+            Writer::Owned(write),
+            RawModeGuard::acquire()?,
+        ))
+    }
+
+    /// Create a renderer for a terminal of a fixed size, without
+    /// enabling raw mode.
+    #[cfg(test)]
+    pub(crate) fn headless(write: &'w mut W, cols: u16, rows: u16) -> Self {
+        Self {
+            size: Some((cols, rows)),
+            ..Self::with_output(Writer::Borrowed(write), RawModeGuard::disabled())
+        }
+    }
+
+    fn with_output(write: Writer<'w, W>, guard: RawModeGuard) -> Self {
         CrosstermRenderer {
-            guard: RawModeGuard::acquire().unwrap(),
-            write,
+            guard,
+            write: Output::new(write),
             draw_state: DrawState::default(),
             margin: NoStyle,
             header: NoStyle,
             footer: NoStyle,
             max_height: None,
+            anchor: Anchor::default(),
+            overflow: Overflow::default(),
+            suffix: None,
+            suffix_on_focus: true,
+            highlighter: None,
+            scrollbar: false,
+            wrap: false,
+            highlight_current_line: false,
+            render_whitespace: false,
+            escape_control: true,
+            cursor_style: None,
+            capture_mouse: false,
+            line_markers: true,
+            osc52_clipboard: false,
+            scroll_off: 0,
+            size: None,
         }
     }
 }
@@ -149,6 +428,135 @@ impl<'w, W, M, H, F> CrosstermRenderer<'w, W, M, H, F> {
     pub fn max_height(self, max_height: Option<usize>) -> Self {
         Self { max_height, ..self }
     }
+
+    /// Set where the frame is anchored in inline mode.
+    pub fn anchor(self, anchor: Anchor) -> Self {
+        Self { anchor, ..self }
+    }
+
+    /// Get the anchor of the content within the last frame drawn,
+    /// for positioning overlays relative to the editor.
+    ///
+    /// This is only valid immediately after a `draw`.
+    pub fn frame_anchor(&self) -> FrameAnchor {
+        FrameAnchor {
+            base_row_offset: self.draw_state.anchor.ln,
+            gutter_col: self.draw_state.anchor.col,
+        }
+    }
+
+    /// Set the text rendered after the content of each line.
+    pub fn inline_suffix(self, suffix: impl InlineSuffix + 'w) -> Self {
+        Self {
+            suffix: Some(Box::new(suffix)),
+            ..self
+        }
+    }
+
+    /// Set the highlighter that colors the text of each line.
+    pub fn highlighter(self, highlighter: impl Highlighter + 'w) -> Self {
+        Self {
+            highlighter: Some(Box::new(highlighter)),
+            ..self
+        }
+    }
+
+    /// Set whether a scrollbar is drawn in the right-most column
+    /// when the content does not fit.
+    pub fn scrollbar(self, scrollbar: bool) -> Self {
+        Self { scrollbar, ..self }
+    }
+
+    /// Set whether lines wider than the terminal are wrapped
+    /// onto multiple rows instead of being truncated.
+    pub fn wrap(self, wrap: bool) -> Self {
+        Self { wrap, ..self }
+    }
+
+    /// Set whether the line being edited is painted with a subtle background.
+    pub fn highlight_current_line(self, highlight_current_line: bool) -> Self {
+        Self {
+            highlight_current_line,
+            ..self
+        }
+    }
+
+    /// Set whether control characters are drawn dimmed in caret notation,
+    /// such as `^A`, which is the default. Otherwise they are written as is,
+    /// and may move the cursor or corrupt the display.
+    pub fn escape_control(self, escape_control: bool) -> Self {
+        Self {
+            escape_control,
+            ..self
+        }
+    }
+
+    /// Set whether spaces and tabs are drawn as dim `·` and `→`,
+    /// with trailing whitespace highlighted.
+    pub fn render_whitespace(self, render_whitespace: bool) -> Self {
+        Self {
+            render_whitespace,
+            ..self
+        }
+    }
+
+    /// Set the shape of the cursor while editing. The terminal default
+    /// is restored when finished.
+    pub fn cursor_style(self, cursor_style: SetCursorStyle) -> Self {
+        Self {
+            cursor_style: Some(cursor_style),
+            ..self
+        }
+    }
+
+    /// Set whether mouse events are captured, so the wheel scrolls
+    /// the view. This disables text selection by the terminal.
+    pub fn capture_mouse(self, capture_mouse: bool) -> Self {
+        Self {
+            capture_mouse,
+            ..self
+        }
+    }
+
+    /// Set whether lines that do not fit are marked, with a `›` at the
+    /// right edge of truncated lines and a `↳` in the gutter beside the
+    /// continuation rows of wrapped lines.
+    pub fn line_markers(self, line_markers: bool) -> Self {
+        Self {
+            line_markers,
+            ..self
+        }
+    }
+
+    /// Set whether copied text is put on the clipboard of the terminal
+    /// with an OSC 52 escape sequence, which also works over SSH.
+    /// Not every terminal supports it, so it is disabled by default.
+    pub fn osc52_clipboard(self, osc52_clipboard: bool) -> Self {
+        Self {
+            osc52_clipboard,
+            ..self
+        }
+    }
+
+    /// Set how content taller than the frame is shown.
+    pub fn overflow(self, overflow: Overflow) -> Self {
+        Self { overflow, ..self }
+    }
+
+    /// Set the number of lines kept visible above and below the focus
+    /// when scrolling, like `scrolloff` in vim. Near the start and end
+    /// of the content, the focus can still reach the edges.
+    pub fn scroll_off(self, scroll_off: usize) -> Self {
+        Self { scroll_off, ..self }
+    }
+
+    /// Set whether the inline suffix is shown on the line being edited.
+    pub fn suffix_on_focus(self, suffix_on_focus: bool) -> Self {
+        Self {
+            suffix_on_focus,
+            ..self
+        }
+    }
 }
 
 // region: Swap constructors
@@ -163,6 +571,22 @@ impl<'w, W, M1, H, F> CrosstermRenderer<'w, W, M1, H, F> {
             header: self.header,
             footer: self.footer,
             max_height: self.max_height,
+            anchor: self.anchor,
+            overflow: self.overflow,
+            suffix: self.suffix,
+            suffix_on_focus: self.suffix_on_focus,
+            highlighter: self.highlighter,
+            scrollbar: self.scrollbar,
+            wrap: self.wrap,
+            highlight_current_line: self.highlight_current_line,
+            render_whitespace: self.render_whitespace,
+            escape_control: self.escape_control,
+            cursor_style: self.cursor_style,
+            capture_mouse: self.capture_mouse,
+            line_markers: self.line_markers,
+            osc52_clipboard: self.osc52_clipboard,
+            scroll_off: self.scroll_off,
+            size: self.size,
         }
     }
 }
@@ -178,6 +602,22 @@ impl<'w, W, M, H1, F> CrosstermRenderer<'w, W, M, H1, F> {
             header,
             footer: self.footer,
             max_height: self.max_height,
+            anchor: self.anchor,
+            overflow: self.overflow,
+            suffix: self.suffix,
+            suffix_on_focus: self.suffix_on_focus,
+            highlighter: self.highlighter,
+            scrollbar: self.scrollbar,
+            wrap: self.wrap,
+            highlight_current_line: self.highlight_current_line,
+            render_whitespace: self.render_whitespace,
+            escape_control: self.escape_control,
+            cursor_style: self.cursor_style,
+            capture_mouse: self.capture_mouse,
+            line_markers: self.line_markers,
+            osc52_clipboard: self.osc52_clipboard,
+            scroll_off: self.scroll_off,
+            size: self.size,
         }
     }
 }
@@ -193,10 +633,38 @@ impl<'w, W, M, H, F1> CrosstermRenderer<'w, W, M, H, F1> {
             header: self.header,
             footer,
             max_height: self.max_height,
+            anchor: self.anchor,
+            overflow: self.overflow,
+            suffix: self.suffix,
+            suffix_on_focus: self.suffix_on_focus,
+            highlighter: self.highlighter,
+            scrollbar: self.scrollbar,
+            wrap: self.wrap,
+            highlight_current_line: self.highlight_current_line,
+            render_whitespace: self.render_whitespace,
+            escape_control: self.escape_control,
+            cursor_style: self.cursor_style,
+            capture_mouse: self.capture_mouse,
+            line_markers: self.line_markers,
+            osc52_clipboard: self.osc52_clipboard,
+            scroll_off: self.scroll_off,
+            size: self.size,
         }
     }
 }
 
+impl<'w, W, M1, H1, F1> CrosstermRenderer<'w, W, M1, H1, F1> {
+    /// Swap out the margin, header and footer formatters with a bundle.
+    pub fn style<M2, H2, F2>(
+        self,
+        style: StyleBundle<M2, H2, F2>,
+    ) -> CrosstermRenderer<'w, W, M2, H2, F2> {
+        self.margin(style.margin)
+            .header(style.header)
+            .footer(style.footer)
+    }
+}
+
 impl<'w, W, M, H, F> CrosstermRenderer<'w, W, M, H, F>
 where
     W: Write,
@@ -204,8 +672,16 @@ where
     H: Header<W>,
     F: Footer<W>,
 {
+    /// Get the columns and rows of the terminal.
+    fn terminal_size(&self) -> io::Result<(u16, u16)> {
+        match self.size {
+            Some(size) => Ok(size),
+            None => crossterm::terminal::size(),
+        }
+    }
+
     fn calculate_draw_range(&self, data: &Editor) -> (usize, usize, usize) {
-        if let Ok((_, rows)) = crossterm::terminal::size() {
+        if let Ok((cols, rows)) = self.terminal_size() {
             // Rows of the terminal.
             let max_height = if !data.altscreen {
                 self.max_height
@@ -214,25 +690,39 @@ where
             };
             let term_rows = max_height
                 .unwrap_or(usize::MAX)
-                .min(rows.try_into().unwrap())
+                .min(rows.into())
                 .saturating_sub(self.header.rows())
                 .saturating_sub(self.footer.rows());
             if term_rows == 0 {
                 return (0, 0, 0);
             }
+            if self.collapses(data, term_rows) {
+                return (0, term_rows - 1, term_rows);
+            }
+            if let Some(top) = data.scroll_top() {
+                let (low, high) = self.scrolled_draw_range(data, top, cols.into(), term_rows);
+                return (low, high, term_rows);
+            }
+            if self.wrap {
+                let (low, high) = self.wrapped_draw_range(data, cols.into(), term_rows);
+                return (low, high, term_rows);
+            }
             // Rows of the data to draw.
             let data_rows = data.line_count();
             // Current line of the data.
             let line = data.selection.focus.ln;
             if data_rows > term_rows {
-                let (low, high) = if line >= self.draw_state.high {
-                    (line - term_rows + 1, line + 1)
-                } else if line < self.draw_state.low {
-                    (line, line + term_rows)
+                // Lines kept visible around the focus.
+                let off = self.scroll_off.min((term_rows - 1) / 2);
+                let low = if line + off >= self.draw_state.high {
+                    (line + off + 1).saturating_sub(term_rows)
+                } else if line < self.draw_state.low + off {
+                    line.saturating_sub(off)
                 } else {
-                    (self.draw_state.low, self.draw_state.high)
+                    self.draw_state.low
                 };
-                (low, high.min(data_rows), term_rows)
+                let low = low.min(data_rows - term_rows);
+                (low, low + term_rows, term_rows)
             } else {
                 (0, data.line_count(), term_rows)
             }
@@ -241,28 +731,154 @@ where
         }
     }
 
+    /// Check if the lines below the frame are collapsed into a row,
+    /// which is while the focus is above that row.
+    fn collapses(&self, data: &Editor, term_rows: usize) -> bool {
+        self.overflow == Overflow::Collapse
+            && !data.altscreen
+            && !self.wrap
+            && data.scroll_top().is_none()
+            && data.line_count() > term_rows
+            && data.selection.focus.ln + 1 < term_rows
+    }
+
+    /// Calculate the range of lines to draw when the view is scrolled
+    /// to a line, regardless of the focus.
+    fn scrolled_draw_range(
+        &self,
+        data: &Editor,
+        top: usize,
+        cols: usize,
+        term_rows: usize,
+    ) -> (usize, usize) {
+        let data_rows = data.line_count();
+        if !self.wrap {
+            // Do not scroll past the point where the last line is at the bottom.
+            let low = top.min(data_rows.saturating_sub(term_rows));
+            return (low, (low + term_rows).min(data_rows));
+        }
+        let width = self.wrap_width(data, cols);
+        let rows_of = |ln| data.wrap_points(ln, width, self.escape_control).len();
+        let low = top.min(data.last_line());
+        let mut rows = rows_of(low);
+        let mut high = low + 1;
+        while high < data_rows && rows + rows_of(high) <= term_rows {
+            rows += rows_of(high);
+            high += 1;
+        }
+        (low, high)
+    }
+
+    /// Calculate the range of lines to draw when wrapping, where
+    /// each line may take up multiple rows.
+    fn wrapped_draw_range(&self, data: &Editor, cols: usize, term_rows: usize) -> (usize, usize) {
+        let width = self.wrap_width(data, cols);
+        let line = data.selection.focus.ln;
+        let rows_of = |ln| data.wrap_points(ln, width, self.escape_control).len();
+        let bottom = (line + self.scroll_off).min(data.last_line());
+
+        // Keep the previous top line if the focus and the lines
+        // kept below it still fit, dropping lines above it first.
+        let mut low = self
+            .draw_state
+            .low
+            .min(line.saturating_sub(self.scroll_off));
+        let mut rows: usize = (low..=bottom).map(rows_of).sum();
+        while rows > term_rows && low < line {
+            rows -= rows_of(low);
+            low += 1;
+        }
+        let mut high = bottom + 1;
+        while rows > term_rows && high > line + 1 {
+            high -= 1;
+            rows -= rows_of(high);
+        }
+
+        // Fill the remaining rows below, then above.
+        while high < data.line_count() && rows + rows_of(high) <= term_rows {
+            rows += rows_of(high);
+            high += 1;
+        }
+        while low > 0 && rows + rows_of(low - 1) <= term_rows {
+            rows += rows_of(low - 1);
+            low -= 1;
+        }
+        (low, high)
+    }
+
+    /// Get the width available for the content of a line.
+    fn wrap_width(&self, data: &Editor, cols: usize) -> usize {
+        cols.saturating_sub(self.margin.width(data))
+            .saturating_sub(self.scrollbar as usize)
+    }
+
+    /// Get the number of rows taken up by a range of lines.
+    fn visual_rows(&self, data: &Editor, low: usize, high: usize) -> usize {
+        if self.wrap {
+            let width = self.wrap_width(data, self.draw_state.cols);
+            (low..high)
+                .map(|ln| data.wrap_points(ln, width, self.escape_control).len())
+                .sum()
+        } else {
+            high - low
+        }
+    }
+
     // Move to the base of the frame (not the anchor).
     fn move_to_frame_base(&mut self) -> Result<()> {
         let up_offset = self.draw_state.anchor.ln + self.draw_state.cursor.ln;
-        self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        self.move_cursor_up(up_offset)?;
         self.write.queue(MoveToColumn(0))?;
         Ok(())
     }
 
+    /// Move the cursor up a number of rows. Terminals treat a
+    /// movement of zero rows as one, so it is skipped.
+    fn move_cursor_up(&mut self, rows: usize) -> Result<()> {
+        if rows > 0 {
+            self.write.queue(MoveUp(Self::usize_to_u16(rows)))?;
+        }
+        Ok(())
+    }
+
     // Position the cursor right after drawing a frame
     // (assuming no other cursor adjustments made).
     fn draw_cursor(&mut self, data: &Editor) -> Result<()> {
         // Move to the correct row.
         let line = data.selection.focus.ln;
         let frame_height = self.draw_state.height;
-        let relative_ln = line - self.draw_state.low;
+        if line < self.draw_state.low || line >= self.draw_state.high {
+            // The focus is scrolled out of view, so leave the cursor
+            // at the end of the frame.
+            if !self.draw_state.cursor_hidden {
+                self.write.queue(Hide)?;
+                self.draw_state.cursor_hidden = true;
+            }
+            self.draw_state.cursor.ln = frame_height - 1 - self.draw_state.anchor.ln;
+            return Ok(());
+        }
+        if self.draw_state.cursor_hidden {
+            self.write.queue(Show)?;
+            self.draw_state.cursor_hidden = false;
+        }
+        let col = data.selection.focus.col.min(data.curr_ln_len());
+        let mut relative_ln = self.visual_rows(data, self.draw_state.low, line);
+        let mut row_start = 0;
+        if self.wrap {
+            let width = self.wrap_width(data, self.draw_state.cols);
+            let points = data.wrap_points(line, width, self.escape_control);
+            let row = points.iter().rposition(|&start| start <= col).unwrap_or(0);
+            relative_ln += row;
+            row_start = points[row];
+        }
         let up_offset = frame_height - 1 - self.draw_state.anchor.ln - relative_ln;
-        // self.move_cursor_up(up_offset)?;
-        self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        self.move_cursor_up(up_offset)?;
 
-        // Move to the correct column.
-        let col = data.selection.focus.col.min(data.curr_ln_len());
-        let n = self.draw_state.anchor.col + col + 1;
+        // Move to the correct column, counting terminal cells
+        // rather than characters.
+        let escape = self.escape_control;
+        let width = data.display_col(line, col, escape) - data.display_col(line, row_start, escape);
+        let n = self.draw_state.anchor.col + width;
         self.write.queue(MoveToColumn(Self::usize_to_u16(n)))?;
 
         self.draw_state.cursor.ln = relative_ln;
@@ -271,41 +887,217 @@ where
         Ok(())
     }
 
+    /// Draw empty rows above the frame, keeping the content
+    /// pinned to the bottom of the drawable area.
+    fn draw_padding(&mut self, rows: usize) -> Result<()> {
+        self.draw_state.height += rows;
+        self.draw_state.anchor.ln += rows;
+
+        for _ in 0..rows {
+            self.cursor_to_left_term_edge()?;
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+            self.write.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
     fn draw_header(&mut self, data: &Editor) -> Result<()> {
         self.draw_state.height += self.header.rows();
         self.draw_state.anchor.ln += self.header.rows();
 
         self.cursor_to_left_term_edge()?;
-        self.header.draw(self.write, data)?;
+        self.header.draw(&mut *self.write, data)?;
         if self.header.rows() > 0 {
-            self.write.write(b"\n")?;
+            self.write.write_all(b"\n")?;
         }
         Ok(())
     }
 
-    /// Draw the line given an index.
+    /// Draw the line given an index, returning the number of rows drawn.
     /// This method does not move the cursor.
-    fn draw_line(&mut self, data: &Editor, line: usize) -> Result<()> {
+    fn draw_line(&mut self, data: &Editor, line: usize, brackets: &[Cursor]) -> Result<usize> {
         self.cursor_to_left_term_edge()?;
 
-        self.margin.draw(self.write, line, data)?;
+        self.margin.draw(&mut *self.write, line, data)?;
+        let thumb = self.scrollbar_thumb(data, line);
+        let background = if self.highlight_current_line && line == data.selection.focus.ln {
+            Some(CURRENT_LINE)
+        } else {
+            None
+        };
+        let spans = match &self.highlighter {
+            Some(highlighter) if line < data.line_count() => highlighter.spans(&data.line(line)),
+            _ => Vec::new(),
+        };
+        let style = LineStyle {
+            background,
+            spans: &spans,
+            whitespace: self.render_whitespace,
+            raw_control: !self.escape_control,
+            brackets,
+        };
+        if self.wrap && line < data.line_count() {
+            let width = self.wrap_width(data, self.draw_state.cols);
+            let points = data.wrap_points(line, width, self.escape_control);
+            for (i, &start) in points.iter().enumerate() {
+                if i > 0 {
+                    // Continuation rows have a blank margin.
+                    self.end_row(thumb, background)?;
+                    self.write.write_all(b"\n")?;
+                    self.cursor_to_left_term_edge()?;
+                    let width = self.margin.width(data);
+                    if self.line_markers && width >= 2 {
+                        write!(self.write, "{:1$}", "", width - 2)?;
+                        write!(self.write, "{} ", "↳".dark_grey())?;
+                    } else {
+                        write!(self.write, "{:1$}", "", width)?;
+                    }
+                }
+                let end = points.get(i + 1).copied().unwrap_or(usize::MAX);
+                data.write_line_styled(line, start..end, &style, &mut *self.write)?;
+            }
+            let last = points[points.len() - 1];
+            let escape = self.escape_control;
+            let len =
+                data.display_col(line, usize::MAX, escape) - data.display_col(line, last, escape);
+            self.draw_suffix(data, line, width.saturating_sub(len))?;
+            self.end_row(thumb, background)?;
+            return Ok(points.len());
+        }
         if line < data.line_count() {
-            data.write_line(line, self.write)?;
+            let width = self
+                .draw_state
+                .cols
+                .saturating_sub(self.margin.width(data))
+                .saturating_sub(thumb.is_some() as usize);
+            let len = data.display_col(line, usize::MAX, self.escape_control);
+            if self.line_markers && width > 0 && len > width {
+                // Leave the last column for the truncation marker.
+                let cols = data.col_within(line, 0, width - 1, self.escape_control);
+                data.write_line_styled(line, 0..cols, &style, &mut *self.write)?;
+                write!(self.write, "{}", "›".dark_grey())?;
+            } else if len > width {
+                let cols = data.col_within(line, 0, width, self.escape_control);
+                data.write_line_styled(line, 0..cols, &style, &mut *self.write)?;
+            } else {
+                data.write_line_styled(line, 0..usize::MAX, &style, &mut *self.write)?;
+                self.draw_suffix(data, line, width.saturating_sub(len))?;
+            }
+        }
+        self.end_row(thumb, background)?;
+        Ok(1)
+    }
+
+    /// Clear the rest of the row with a background
+    /// and draw the scrollbar cell.
+    fn end_row(&mut self, thumb: Option<bool>, background: Option<Color>) -> Result<()> {
+        if let Some(color) = background {
+            self.write.queue(SetBackgroundColor(color))?;
         }
         self.write.queue(Clear(ClearType::UntilNewLine))?;
+        if background.is_some() {
+            self.write.queue(ResetColor)?;
+        }
+
+        if let Some(thumb) = thumb {
+            let col = Self::usize_to_u16(self.draw_state.cols.saturating_sub(1));
+            self.write.queue(MoveToColumn(col))?;
+            if thumb {
+                write!(self.write, "{}", "┃".grey())?;
+            } else {
+                write!(self.write, "{}", "│".dark_grey())?;
+            }
+        }
 
         Ok(())
     }
 
+    /// Get whether the row of the scrollbar beside a line is part of
+    /// the thumb, or `None` if the scrollbar is not drawn.
+    fn scrollbar_thumb(&self, data: &Editor, line: usize) -> Option<bool> {
+        let total = data.line_count();
+        let rows = self.draw_state.rows;
+        if !self.scrollbar || total <= rows {
+            return None;
+        }
+        let start = self.draw_state.low * rows / total;
+        let len = (rows * rows / total).max(1);
+        let row = line - self.draw_state.low;
+        Some(start <= row && row < start + len)
+    }
+
+    /// Get the terminal column, first row and number of rows of the
+    /// scrollbar in the last frame drawn, if one was drawn.
+    ///
+    /// An inline frame has no fixed row, so it is found by querying
+    /// the cursor position, which is only done when the mouse is captured.
+    fn scrollbar_track(&self, data: &Editor) -> Option<(u16, u16, usize)> {
+        if self.draw_state.cols == 0 {
+            return None;
+        }
+        self.scrollbar_thumb(data, self.draw_state.low)?;
+        let top = if self.draw_state.altscreen {
+            self.draw_state.anchor.ln
+        } else if self.capture_mouse && self.size.is_none() {
+            let (_, row) = position().ok()?;
+            usize::from(row).checked_sub(self.draw_state.cursor.ln)?
+        } else {
+            return None;
+        };
+        Some((
+            Self::usize_to_u16(self.draw_state.cols - 1),
+            Self::usize_to_u16(top),
+            self.draw_state.rows,
+        ))
+    }
+
+    /// Draw the inline suffix of a line, clipped to the remaining width.
+    fn draw_suffix(&mut self, data: &Editor, line: usize, width: usize) -> Result<()> {
+        if !self.suffix_on_focus && line == data.selection.focus.ln {
+            return Ok(());
+        }
+        if let Some((text, color)) = self.suffix.as_ref().and_then(|s| s.suffix(line, data)) {
+            self.write.queue(SetForegroundColor(color))?;
+            let mut cells = 0;
+            for c in " ".chars().chain(text.chars()) {
+                cells += char_width(c, false);
+                if cells > width {
+                    break;
+                }
+                write!(self.write, "{}", c)?;
+            }
+            self.write.queue(ResetColor)?;
+        }
+        Ok(())
+    }
+
     fn draw_footer(&mut self, data: &Editor) -> Result<()> {
         self.draw_state.height += self.footer.rows();
 
         self.cursor_to_left_term_edge()?;
         if self.footer.rows() > 0 {
-            self.write.write(b"\n")?;
+            self.write.write_all(b"\n")?;
         }
         // write!(self.write, "{} {} {}", self.draw_state.low, self.draw_state.high, data.cursor.ln)?;
-        self.footer.draw(self.write, data)?;
+        self.footer.draw(&mut *self.write, data)?;
+        Ok(())
+    }
+
+    /// Draw the row that stands in for the lines below the frame.
+    fn draw_collapsed(&mut self, data: &Editor, high: usize) -> Result<()> {
+        self.draw_state.height += 1;
+
+        self.write.write_all(b"\n")?;
+        self.cursor_to_left_term_edge()?;
+        let width = self.margin.width(data);
+        write!(self.write, "{:1$}", "", width)?;
+        let label = format!("… ({} more lines)", data.line_count() - high);
+        let label: String = label
+            .chars()
+            .take(self.draw_state.cols.saturating_sub(width))
+            .collect();
+        write!(self.write, "{}", label.dark_grey())?;
+        self.write.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
 
@@ -316,29 +1108,37 @@ where
         high: usize,
         term_rows: usize,
     ) -> Result<()> {
+        self.draw_state.low = low;
+        self.draw_state.high = high;
+
         // Print out the contents.
+        let brackets = data.bracket_pair();
+        let mut rows = 0;
         for i in low..high {
-            self.draw_line(&data, i)?;
+            rows += self.draw_line(data, i, &brackets)?;
             if i < high - 1 {
                 // The last line should not have any new-line attached to it.
-                self.write.write(b"\n")?;
+                self.write.write_all(b"\n")?;
             }
         }
 
-        self.draw_state.anchor.col = self.margin.width();
-        self.draw_state.low = low;
-        self.draw_state.high = high;
-        self.draw_state.height += high - low;
-        self.draw_state.cursor.ln = high - low - 1;
-        self.draw_state.cursor.col = data.line(high - 1).len();
+        self.draw_state.anchor.col = self.margin.width(data);
+        self.draw_state.height += rows;
+        self.draw_state.cursor.ln = rows.saturating_sub(1);
+        self.draw_state.cursor.col = if high > low {
+            data.line(high - 1).len()
+        } else {
+            0
+        };
 
         if data.altscreen {
-            for i in high..low + term_rows {
-                self.write.write(b"\n")?;
-                self.draw_line(&data, i)?;
+            let padding = term_rows.saturating_sub(rows);
+            for i in high..high + padding {
+                self.write.write_all(b"\n")?;
+                self.draw_line(data, i, &brackets)?;
             }
-            self.draw_state.height += low + term_rows - high;
-            self.draw_state.cursor.ln += low + term_rows - high;
+            self.draw_state.height += padding;
+            self.draw_state.cursor.ln += padding;
         }
 
         Ok(())
@@ -352,15 +1152,275 @@ where
 
     #[doc(hidden)]
     fn usize_to_u16(n: usize) -> u16 {
-        n.try_into().unwrap_or(std::u16::MAX)
+        n.try_into().unwrap_or(u16::MAX)
     }
 }
 
 pub type DefaultRenderer<'w, W> = CrosstermRenderer<'w, W, NoStyle, NoStyle, NoStyle>;
 
 impl Default for DefaultRenderer<'static, Stdout> {
+    /// Create a renderer that owns a handle to stdout.
+    ///
+    /// # Panics
+    /// Panics if raw mode cannot be enabled.
     fn default() -> Self {
-        let out = Box::new(stdout());
-        CrosstermRenderer::render_to(Box::leak(out))
+        Self::render_owned(stdout())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::styles::classic;
+
+    /// Draw a frame of an editor and get the output of that frame.
+    fn drawn<M, H, F>(
+        renderer: &mut CrosstermRenderer<'_, Vec<u8>, M, H, F>,
+        data: &Editor,
+    ) -> String
+    where
+        M: Margin<Vec<u8>>,
+        H: Header<Vec<u8>>,
+        F: Footer<Vec<u8>>,
+    {
+        renderer.write.clear();
+        renderer.draw(data).unwrap();
+        String::from_utf8_lossy(&renderer.write).into_owned()
+    }
+
+    #[test]
+    fn suffix_is_clipped_by_display_width() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 10, 5)
+            .inline_suffix(|_, _: &Editor| Some(("日本語です".to_string(), Color::Grey)));
+        let frame = drawn(&mut renderer, &Editor::from("abc"));
+        assert!(frame.contains("abc"));
+        assert!(frame.contains(" 日本語"));
+        assert!(!frame.contains('で'));
+    }
+
+    #[test]
+    fn status_is_drawn_in_the_footer() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10).style(classic::bundle("x"));
+        let mut editor = Editor::from("abc");
+        editor.set_status("Copied");
+        assert!(drawn(&mut renderer, &editor).contains("Copied"));
+    }
+
+    #[test]
+    fn draws_an_empty_buffer() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10).style(classic::bundle("x"));
+        let mut editor = Editor::default();
+        editor.move_to_bottom();
+        assert!(drawn(&mut renderer, &editor).contains("Lines: 1"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn try_render_to_fails_without_a_terminal() {
+        use std::fs::OpenOptions;
+
+        let has_tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .is_ok();
+        let mut out = Vec::new();
+        let result = DefaultRenderer::try_render_to(&mut out);
+        match result {
+            Err(err) => assert!(matches!(err, crate::error::Error::Terminal(_))),
+            // Raw mode can only be enabled when run from a terminal.
+            Ok(_) => assert!(has_tty),
+        }
+    }
+
+    #[test]
+    fn scroll_off_keeps_lines_around_the_focus() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10).scroll_off(3);
+        let text: Vec<String> = (0..100).map(|ln| ln.to_string()).collect();
+        let mut editor = Editor::from(text.join("\n").as_str());
+        let mut window = |renderer: &mut DefaultRenderer<'_, Vec<u8>>, ln| {
+            editor.selection.focus = Cursor { ln, col: 0 };
+            drawn(renderer, &editor);
+            editor.viewport()
+        };
+        assert_eq!(window(&mut renderer, 0), 0..10);
+        // Moving down scrolls once the focus is within 3 lines of the bottom.
+        assert_eq!(window(&mut renderer, 6), 0..10);
+        assert_eq!(window(&mut renderer, 50), 44..54);
+        // Moving up scrolls once the focus is within 3 lines of the top.
+        assert_eq!(window(&mut renderer, 47), 44..54);
+        assert_eq!(window(&mut renderer, 46), 43..53);
+        // The window is clamped near the ends of the buffer.
+        assert_eq!(window(&mut renderer, 99), 90..100);
+        assert_eq!(window(&mut renderer, 1), 0..10);
+    }
+
+    #[test]
+    fn cursor_column_follows_the_control_escaping() {
+        let mut editor = Editor::from("\x01ab");
+        editor.move_to_line_end(false);
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10);
+        assert!(drawn(&mut renderer, &editor).contains(&format!("{}", MoveToColumn(4))));
+        let mut renderer = renderer.escape_control(false);
+        let frame = drawn(&mut renderer, &editor);
+        assert!(frame.contains('\x01'));
+        assert!(frame.contains(&format!("{}", MoveToColumn(2))));
+    }
+
+    #[test]
+    fn cursor_sits_after_wide_characters() {
+        let mut editor = Editor::from("你好world");
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10);
+        let mut cursor_at = |col| {
+            editor.selection.focus = Cursor { ln: 0, col };
+            drawn(&mut renderer, &editor)
+        };
+        // After 好, each wide character taking two cells.
+        let frame = cursor_at(2);
+        assert!(frame.contains(&format!("{}", MoveToColumn(4))));
+        assert!(!frame.contains(&format!("{}", MoveToColumn(2))));
+        assert!(cursor_at(3).contains(&format!("{}", MoveToColumn(5))));
+        assert!(cursor_at(7).contains(&format!("{}", MoveToColumn(9))));
+    }
+
+    /// Get the escape sequence of a command.
+    fn ansi(command: impl crossterm::Command) -> String {
+        let mut out = String::new();
+        command.write_ansi(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn toggling_the_altscreen_enters_and_leaves_it() {
+        let enter = ansi(EnterAlternateScreen);
+        let leave = ansi(LeaveAlternateScreen);
+        let clear = format!("{}", Clear(ClearType::FromCursorDown));
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10);
+        let mut editor = Editor::from("ab\ncd");
+
+        let frame = drawn(&mut renderer, &editor);
+        assert!(!frame.contains(&enter) && !frame.contains(&leave));
+
+        editor.toggle_altscreen();
+        let frame = drawn(&mut renderer, &editor);
+        let entered = frame.find(&enter).unwrap();
+        assert!(entered < frame.find("ab").unwrap());
+        // Later frames stay on the alternate screen.
+        let frame = drawn(&mut renderer, &editor);
+        assert!(frame.starts_with(&format!("{}", MoveTo(0, 0))));
+        assert!(!frame.contains(&enter));
+
+        editor.toggle_altscreen();
+        let frame = drawn(&mut renderer, &editor);
+        assert!(!frame.contains(&enter));
+        // The inline frame is redrawn after leaving, clearing below it.
+        let left = frame.find(&leave).unwrap();
+        let text = frame.find("ab").unwrap();
+        assert!(left < text && text < frame.rfind(&clear).unwrap());
+        assert!(!drawn(&mut renderer, &editor).contains(&leave));
+    }
+
+    #[test]
+    fn collapse_hides_lines_below_the_frame() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 5).overflow(Overflow::Collapse);
+        let text: Vec<String> = (0..10).map(|ln| format!("line{}", ln)).collect();
+        let mut editor = Editor::from(text.join("\n").as_str());
+
+        editor.selection.focus = Cursor { ln: 3, col: 0 };
+        let frame = drawn(&mut renderer, &editor);
+        assert!(frame.contains("line3") && !frame.contains("line4"));
+        assert!(frame.contains("… (6 more lines)"));
+        assert_eq!(editor.viewport(), 0..4);
+
+        // Moving into the collapsed lines scrolls instead.
+        editor.selection.focus = Cursor { ln: 4, col: 0 };
+        let frame = drawn(&mut renderer, &editor);
+        assert!(frame.contains("line4") && !frame.contains("more lines"));
+        assert_eq!(editor.viewport(), 0..5);
+        editor.selection.focus = Cursor { ln: 9, col: 0 };
+        drawn(&mut renderer, &editor);
+        assert_eq!(editor.viewport(), 5..10);
+    }
+
+    #[test]
+    fn clicking_and_dragging_the_scrollbar_scrolls() {
+        use crate::editor::keybindings::{Keybinding, NormalKeybinding};
+        use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+        let mouse = |kind, column, row| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 12).scrollbar(true);
+        let text: Vec<String> = (0..100).map(|ln| ln.to_string()).collect();
+        let mut editor = Editor::from(text.join("\n").as_str());
+        editor.altscreen = true;
+        drawn(&mut renderer, &editor);
+
+        let top = (0..12).find(|&row| editor.on_scrollbar(79, row)).unwrap();
+        let rows = (top..12)
+            .take_while(|&row| editor.on_scrollbar(79, row))
+            .count();
+        assert!(!editor.on_scrollbar(78, top));
+
+        let mut keybinding = NormalKeybinding::default();
+        let left = MouseButton::Left;
+        let events = vec![
+            mouse(MouseEventKind::Down(left), 79, top + 2),
+            mouse(MouseEventKind::Drag(left), 40, top + 4),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.scroll_top(), Some(4 * 100 / rows));
+        drawn(&mut renderer, &editor);
+        assert_eq!(editor.viewport().start, 4 * 100 / rows);
+
+        // Dragging after the button is released does nothing.
+        let events = vec![
+            mouse(MouseEventKind::Up(left), 40, top + 4),
+            mouse(MouseEventKind::Drag(left), 79, top),
+        ];
+        editor.replay(&mut keybinding, events).unwrap();
+        assert_eq!(editor.scroll_top(), Some(4 * 100 / rows));
+
+        // Clicking beside the scrollbar does not scroll.
+        keybinding
+            .process_event(&mut editor, mouse(MouseEventKind::Down(left), 78, top))
+            .unwrap();
+        assert_eq!(editor.scroll_top(), Some(4 * 100 / rows));
+    }
+
+    #[test]
+    fn scrollbar_is_skipped_without_columns() {
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 0, 5).scrollbar(true);
+        let text: Vec<String> = (0..20).map(|ln| ln.to_string()).collect();
+        let editor = Editor::from(text.join("\n").as_str());
+        drawn(&mut renderer, &editor);
+        assert!(!editor.on_scrollbar(0, 0));
+    }
+
+    #[test]
+    fn copied_text_is_sent_to_the_terminal_only_when_enabled() {
+        let mut editor = Editor::from("foobar");
+        editor.set_copied("foobar");
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10);
+        assert!(!drawn(&mut renderer, &editor).contains("\x1b]52;"));
+        let mut out = Vec::new();
+        let mut renderer = DefaultRenderer::headless(&mut out, 80, 10).osc52_clipboard(true);
+        assert!(drawn(&mut renderer, &editor).contains("\x1b]52;c;Zm9vYmFy\x07"));
     }
 }
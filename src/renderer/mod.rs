@@ -1,10 +1,19 @@
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    ops::Range,
+};
 
-use crate::{editor::Editor, util::trimmed};
+use crate::{
+    editor::{selection::Cursor, Editor},
+    util::{char_width, control_repr, display_width, is_rtl, trimmed},
+};
 
 /// Full renderer.
 pub mod full;
 
+/// In-memory renderer.
+pub mod string;
+
 /// Preset styles.
 pub mod styles;
 
@@ -15,60 +24,453 @@ pub trait Renderer {
     fn clear_draw(&mut self) -> Result<()>;
     fn flush(&mut self) -> Result<()>;
     fn finish(self) -> Result<()>;
+
+    /// Forget the geometry of the drawn frame after the terminal is
+    /// resized, so that the next draw starts afresh.
+    fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        let _ = (cols, rows);
+        Ok(())
+    }
 }
 
 use crossterm::{
-    style::{Color, ResetColor, SetBackgroundColor},
+    style::{Attribute, Color, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
     QueueableCommand,
 };
 use ropey::RopeSlice;
 
+/// Left-to-right override, forces the terminal to display the
+/// following text in logical order.
+const LRO: &str = "\u{202D}";
+/// Pop directional formatting, ends the override.
+const PDF: &str = "\u{202C}";
+
+/// Style of a line written by the renderer.
+#[derive(Default)]
+pub(crate) struct LineStyle<'s> {
+    /// Background of text that is not otherwise highlighted.
+    pub background: Option<Color>,
+    /// Foreground colors of column ranges.
+    pub spans: &'s [(Range<usize>, Color)],
+    /// Whether whitespace characters are made visible.
+    pub whitespace: bool,
+    /// Whether control characters are written as is, instead of in
+    /// caret notation, even though they may corrupt the display.
+    pub raw_control: bool,
+    /// The bracket under the focus and its matching bracket,
+    /// found once per frame.
+    pub brackets: &'s [Cursor],
+}
+
 impl Editor {
+    /// Write the line given an index.
+    ///
+    /// The editor does not support bidirectional text. Lines with
+    /// right-to-left characters are wrapped in a left-to-right override,
+    /// so that the display matches the logical cursor positions.
     pub fn write_line(&self, line_idx: usize, write: &mut dyn Write) -> Result<()> {
-        fn write_rope(write: &mut dyn Write, rope: RopeSlice<'_>) -> io::Result<()> {
-            rope.chunks()
-                .map(|c| c.as_bytes())
-                .try_for_each(|c| write.write_all(c))
+        self.write_line_cols(line_idx, 0..usize::MAX, write)
+    }
+
+    /// Write the given column range of the line given an index.
+    pub fn write_line_cols(
+        &self,
+        line_idx: usize,
+        cols: Range<usize>,
+        write: &mut dyn Write,
+    ) -> Result<()> {
+        let style = LineStyle {
+            brackets: &self.bracket_pair(),
+            ..LineStyle::default()
+        };
+        self.write_line_styled(line_idx, cols, &style, write)
+    }
+
+    /// Write the given column range of the line given an index with a style.
+    pub(crate) fn write_line_styled(
+        &self,
+        line_idx: usize,
+        cols: Range<usize>,
+        style: &LineStyle<'_>,
+        write: &mut dyn Write,
+    ) -> Result<()> {
+        let line = trimmed(self.buf.line(line_idx));
+        let len = line.len_chars();
+        let (low, high) = (cols.start.min(len), cols.end.min(len));
+
+        let mut trailing_start = len;
+        while trailing_start > 0 && line.char(trailing_start - 1).is_whitespace() {
+            trailing_start -= 1;
         }
 
-        if let Some(anchor) = self.selection.anchor {
-            let (mut start, mut end) = (
-                self.selection.focus.min(anchor),
-                self.selection.focus.max(anchor),
-            );
-            let line = trimmed(self.buf.line(line_idx));
-            if start.ln < line_idx && line_idx < end.ln {
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line)?;
-                write.queue(ResetColor)?;
-                return Ok(());
-            } else if start.ln == end.ln && line_idx == start.ln {
-                write_rope(write, line.slice(..start.col))?;
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write!(write, "{}", crossterm::style::Attribute::Bold)?;
-                write_rope(write, line.slice(start.col..end.col))?;
-                write.queue(ResetColor)?;
-                write_rope(write, line.slice(end.col..))?;
+        // Write the text, expanding tabs to the next tab stop.
+        let tab_width = self.tab_width();
+        let whitespace = style.whitespace;
+        let escape = !style.raw_control;
+        let mut display_col = self.display_col(line_idx, low, escape);
+        let mut write_rope = |write: &mut dyn Write, rope: RopeSlice<'_>| -> io::Result<()> {
+            for chunk in rope.chunks() {
+                for (i, part) in chunk.split('\t').enumerate() {
+                    if i > 0 {
+                        let spaces = tab_width - display_col % tab_width;
+                        if whitespace {
+                            write!(write, "→{:1$}", "", spaces - 1)?;
+                        } else {
+                            write!(write, "{:1$}", "", spaces)?;
+                        }
+                        display_col += spaces;
+                    }
+                    write_text(write, part, whitespace, escape)?;
+                    display_col += display_width(part.chars(), escape);
+                }
+            }
+            Ok(())
+        };
 
+        let line = line.slice(low..high);
+
+        let rtl = line.chars().any(is_rtl);
+        if rtl {
+            write.write_all(LRO.as_bytes())?;
+        }
+
+        // Paint the spans, the selection and the matching bracket pair,
+        // writing runs of columns that share a foreground, a background
+        // and a weight. Selections within a line are also bold.
+        let selection = self.selection_cols(line_idx);
+        let bold_selection = self.block().is_none() && !self.selects_lines();
+        let brackets: Vec<usize> = style
+            .brackets
+            .iter()
+            .filter(|cursor| cursor.ln == line_idx)
+            .map(|cursor| cursor.col)
+            .collect();
+        let shown = |col: usize| whitespace && line.char(col - low).is_whitespace();
+        let painted = |col: usize| {
+            let selected = selection.is_some_and(|(start, end)| start <= col && col < end);
+            let fg = if shown(col) {
+                Some(Color::DarkGrey)
+            } else {
+                style
+                    .spans
+                    .iter()
+                    .rev()
+                    .find(|(range, _)| range.contains(&col))
+                    .map(|&(_, color)| color)
+            };
+            let bg = if brackets.contains(&col) {
+                Some(Color::DarkCyan)
+            } else if selected {
+                Some(Color::DarkGrey)
+            } else if shown(col) && col >= trailing_start {
+                Some(Color::DarkRed)
+            } else {
+                style.background
+            };
+            (fg, bg, selected && bold_selection)
+        };
+
+        let mut start = low;
+        while start < high {
+            let (fg, bg, bold) = painted(start);
+            let end = (start + 1..high)
+                .find(|&col| painted(col) != (fg, bg, bold))
+                .unwrap_or(high);
+            if let Some(color) = fg {
+                write.queue(SetForegroundColor(color))?;
+            }
+            if let Some(color) = bg {
+                write.queue(SetBackgroundColor(color))?;
+            }
+            if bold {
+                write.queue(SetAttribute(Attribute::Bold))?;
+            }
+            write_rope(write, line.slice(start - low..end - low))?;
+            if bold {
+                write.queue(SetAttribute(Attribute::NormalIntensity))?;
+            }
+            if fg.is_some() || bg.is_some() {
                 write.queue(ResetColor)?;
-                return Ok(());
-            } else if line_idx == start.ln {
-                start.col = start.col.clamp(0, line.len_chars());
-                write_rope(write, line.slice(..start.col))?;
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line.slice(start.col..))?;
-                write.queue(ResetColor)?;
-                return Ok(());
-            } else if line_idx == end.ln {
-                end.col = end.col.clamp(0, line.len_chars());
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line.slice(..end.col))?;
-                write.queue(ResetColor)?;
-                write_rope(write, line.slice(end.col..))?;
-                return Ok(());
             }
+            start = end;
+        }
+
+        if rtl {
+            write.write_all(PDF.as_bytes())?;
         }
-        write_rope(write, trimmed(self.buf.line(line_idx)))?;
         Ok(())
     }
+
+    /// Get the number of terminal cells before a column of the line
+    /// given an index, expanding tabs and counting wide characters.
+    /// Control characters are counted as drawn in caret notation
+    /// if `escape`, or else as drawn raw.
+    pub fn display_col(&self, line_idx: usize, col: usize, escape: bool) -> usize {
+        let tab_width = self.tab_width();
+        trimmed(self.buf.line(line_idx))
+            .chars()
+            .take(col)
+            .fold(0, |width, c| {
+                width + cell_width(c, width, tab_width, escape)
+            })
+    }
+
+    /// Get the last column of the line given an index such that the
+    /// text from the start column fits within a number of terminal cells.
+    pub(crate) fn col_within(
+        &self,
+        line_idx: usize,
+        start: usize,
+        cells: usize,
+        escape: bool,
+    ) -> usize {
+        let tab_width = self.tab_width();
+        let mut width = 0;
+        let mut base = 0;
+        let line = trimmed(self.buf.line(line_idx));
+        for (col, c) in line.chars().enumerate() {
+            if col == start {
+                base = width;
+            }
+            let next = width + cell_width(c, width, tab_width, escape);
+            if col >= start && next - base > cells {
+                return col;
+            }
+            width = next;
+        }
+        line.len_chars().max(start)
+    }
+
+    /// Get the columns of the line given an index that overlap a range
+    /// of terminal cells, or `None` if the line ends before the range.
+    ///
+    /// An empty range gives the empty column range where text typed
+    /// at the cell would be inserted. Control characters are counted
+    /// as escaped, like the block selection of the editor.
+    pub(crate) fn block_cols(&self, line_idx: usize, cells: Range<usize>) -> Option<Range<usize>> {
+        let tab_width = self.tab_width();
+        let mut width = 0;
+        let mut start = None;
+        let mut end = None;
+        for (col, c) in trimmed(self.buf.line(line_idx)).chars().enumerate() {
+            let next = width + cell_width(c, width, tab_width, true);
+            if start.is_none() && (next > cells.start || width >= cells.start) {
+                start = Some(col);
+            }
+            if width >= cells.end {
+                end = Some(col);
+                break;
+            }
+            width = next;
+        }
+        let len = self.line_len(line_idx);
+        let start = match start {
+            Some(start) => start,
+            None if width >= cells.start => len,
+            None => return None,
+        };
+        if cells.is_empty() {
+            return Some(start..start);
+        }
+        Some(start..end.unwrap_or(len).max(start))
+    }
+
+    /// Get the columns where each row of the line given an index
+    /// starts when wrapped to a number of terminal cells.
+    ///
+    /// If the focus is at the end of the line and the last row is full,
+    /// an empty row is added for the cursor.
+    pub(crate) fn wrap_points(&self, line_idx: usize, cells: usize, escape: bool) -> Vec<usize> {
+        let mut points = vec![0];
+        if cells == 0 {
+            return points;
+        }
+        let tab_width = self.tab_width();
+        let mut width = 0;
+        let mut row_width = 0;
+        let mut len = 0;
+        for (col, c) in trimmed(self.buf.line(line_idx)).chars().enumerate() {
+            let cell = cell_width(c, width, tab_width, escape);
+            // Always make progress, even if a character is too wide.
+            if row_width + cell > cells && row_width > 0 {
+                points.push(col);
+                row_width = 0;
+            }
+            width += cell;
+            row_width += cell;
+            len = col + 1;
+        }
+        let focus = self.clamp_cursor(self.selection.focus);
+        if focus.ln == line_idx && focus.col == len && len > 0 && row_width >= cells {
+            points.push(len);
+        }
+        points
+    }
+
+    /// Get the positions of the bracket under the focus and its
+    /// matching bracket, or nothing if there is no matching bracket.
+    pub(crate) fn bracket_pair(&self) -> Vec<Cursor> {
+        match self.matching_bracket() {
+            Some(other) => vec![self.clamp_cursor(self.selection.focus), other],
+            None => Vec::new(),
+        }
+    }
+
+    /// Get the selected column range of the line given an index.
+    fn selection_cols(&self, line_idx: usize) -> Option<(usize, usize)> {
+        if let Some((lines, cells)) = self.block() {
+            if !lines.contains(&line_idx) {
+                return None;
+            }
+            return self
+                .block_cols(line_idx, cells)
+                .map(|cols| (cols.start, cols.end));
+        }
+        let anchor = self.selection.anchor?;
+        let start = self.selection.focus.min(anchor);
+        let end = self.selection.focus.max(anchor);
+        if line_idx < start.ln || end.ln < line_idx {
+            return None;
+        }
+        let start_col = if line_idx == start.ln { start.col } else { 0 };
+        let end_col = if line_idx == end.ln {
+            end.col
+        } else {
+            usize::MAX
+        };
+        Some((start_col, end_col))
+    }
+}
+
+/// Get the number of terminal cells a character occupies
+/// when drawn at a display column.
+fn cell_width(c: char, display_col: usize, tab_width: usize, escape: bool) -> usize {
+    match c {
+        '\t' => tab_width - display_col % tab_width,
+        c => char_width(c, escape),
+    }
+}
+
+/// Write text without tabs, drawing spaces as `·` if `whitespace`,
+/// and control characters dimmed in caret notation if `escape`.
+fn write_text(write: &mut dyn Write, text: &str, whitespace: bool, escape: bool) -> io::Result<()> {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = if escape {
+            rest.find(char::is_control).unwrap_or(rest.len())
+        } else {
+            rest.len()
+        };
+        let (plain, control) = rest.split_at(end);
+        if whitespace {
+            write.write_all(plain.replace(' ', "·").as_bytes())?;
+        } else {
+            write.write_all(plain.as_bytes())?;
+        }
+        let mut chars = control.chars();
+        if let Some(repr) = chars.next().and_then(control_repr) {
+            write.queue(SetAttribute(Attribute::Dim))?;
+            write.write_all(repr.as_bytes())?;
+            write.queue(SetAttribute(Attribute::NormalIntensity))?;
+        }
+        rest = chars.as_str();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOLD: &str = "\x1b[1m";
+
+    fn written(editor: &Editor, line_idx: usize) -> String {
+        let mut out = Vec::new();
+        editor.write_line(line_idx, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn selection_within_a_line_is_bold() {
+        let mut editor = Editor::from("abcd");
+        editor.set_selection(Cursor { ln: 0, col: 1 }, Cursor { ln: 0, col: 3 });
+        let line = written(&editor, 0);
+        let bold = line.find(BOLD).unwrap();
+        assert!(line[..bold].contains('a'));
+        assert!(line[bold..].starts_with(&format!("{}bc", BOLD)));
+    }
+
+    #[test]
+    fn selection_across_lines_is_not_bold() {
+        let mut editor = Editor::from("abcd\nefgh");
+        editor.set_selection(Cursor { ln: 0, col: 1 }, Cursor { ln: 1, col: 2 });
+        assert!(!written(&editor, 0).contains(BOLD));
+        assert!(!written(&editor, 1).contains(BOLD));
+    }
+
+    #[test]
+    fn matching_brackets_are_highlighted() {
+        let mut editor = Editor::from("f(a)\n[b]");
+        editor.selection.focus = Cursor { ln: 0, col: 1 };
+        assert_eq!(
+            editor.bracket_pair(),
+            vec![Cursor { ln: 0, col: 1 }, Cursor { ln: 0, col: 3 }]
+        );
+        let cyan = format!("{}", SetBackgroundColor(Color::DarkCyan));
+        assert_eq!(written(&editor, 0).matches(&cyan).count(), 2);
+        assert!(!written(&editor, 1).contains(&cyan));
+
+        editor.selection.focus = Cursor { ln: 0, col: 2 };
+        assert!(editor.bracket_pair().is_empty());
+    }
+
+    #[test]
+    fn wrap_points_count_cells() {
+        let editor = Editor::from("abcdefg\n日本語です");
+        assert_eq!(editor.wrap_points(0, 3, true), vec![0, 3, 6]);
+        assert_eq!(editor.wrap_points(1, 5, true), vec![0, 2, 4]);
+        // A character wider than the row still takes a row of its own.
+        assert_eq!(editor.wrap_points(1, 1, true), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn wrap_points_add_a_row_for_the_cursor_at_a_full_end() {
+        let mut editor = Editor::from("abcdef");
+        assert_eq!(editor.wrap_points(0, 3, true), vec![0, 3]);
+        editor.move_to_line_end(false);
+        assert_eq!(editor.wrap_points(0, 3, true), vec![0, 3, 6]);
+        assert_eq!(editor.wrap_points(0, 4, true), vec![0, 4]);
+    }
+
+    #[test]
+    fn col_within_counts_from_the_start_column() {
+        let editor = Editor::from("ab日本c");
+        assert_eq!(editor.col_within(0, 0, 3, true), 2);
+        assert_eq!(editor.col_within(0, 0, 4, true), 3);
+        assert_eq!(editor.col_within(0, 2, 4, true), 4);
+        assert_eq!(editor.col_within(0, 3, 10, true), 5);
+        assert_eq!(editor.col_within(0, 7, 10, true), 7);
+    }
+
+    #[test]
+    fn control_characters_are_not_written_raw() {
+        let editor = Editor::from("a\x01b\x1bc");
+        let line = written(&editor, 0);
+        assert!(!line.contains('\x01'));
+        // Escapes are only written to start styling sequences.
+        assert!(line
+            .match_indices('\x1b')
+            .all(|(i, _)| line[i + 1..].starts_with('[')));
+        assert!(line.contains("^A") && line.contains("^["));
+    }
+
+    #[test]
+    fn control_width_depends_on_escaping() {
+        let editor = Editor::from("a\x01b\x1bc");
+        assert_eq!(editor.display_col(0, 5, true), 7);
+        assert_eq!(editor.display_col(0, 5, false), 3);
+        assert_eq!(editor.wrap_points(0, 3, true), vec![0, 2, 4]);
+        assert_eq!(editor.wrap_points(0, 3, false), vec![0]);
+        assert_eq!(editor.col_within(0, 0, 2, false), 4);
+    }
 }
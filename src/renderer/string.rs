@@ -0,0 +1,231 @@
+use std::io::Write;
+
+use super::{
+    styles::{Footer, Header, Margin, NoStyle, StyleBundle},
+    Editor, LineStyle, Renderer,
+};
+use crate::Result;
+
+/// Renderer that writes frames into an in-memory buffer instead of a
+/// terminal, without raw mode or cursor movement.
+///
+/// Each draw replaces the buffer with the new frame, and the last frame
+/// is kept after finishing, so the visible output of an editor state
+/// can be inspected, such as in tests.
+pub struct StringRenderer<'w, M, H, F> {
+    write: &'w mut Vec<u8>,
+    margin: M,
+    header: H,
+    footer: F,
+}
+
+impl<'w> StringRenderer<'w, NoStyle, NoStyle, NoStyle> {
+    pub fn render_to(write: &'w mut Vec<u8>) -> Self {
+        StringRenderer {
+            write,
+            margin: NoStyle,
+            header: NoStyle,
+            footer: NoStyle,
+        }
+    }
+}
+
+impl<M, H, F> Renderer for StringRenderer<'_, M, H, F>
+where
+    M: Margin<Vec<u8>>,
+    H: Header<Vec<u8>>,
+    F: Footer<Vec<u8>>,
+{
+    /// Draw the frame into the buffer, one row per line.
+    fn draw(&mut self, data: &Editor) -> Result<()> {
+        self.write.clear();
+
+        if self.header.rows() > 0 {
+            self.header.draw(self.write, data)?;
+            self.write.write_all(b"\n")?;
+        }
+        let style = LineStyle {
+            brackets: &data.bracket_pair(),
+            ..LineStyle::default()
+        };
+        for line in 0..data.line_count() {
+            if line > 0 {
+                self.write.write_all(b"\n")?;
+            }
+            self.margin.draw(self.write, line, data)?;
+            data.write_line_styled(line, 0..usize::MAX, &style, self.write)?;
+        }
+        if self.footer.rows() > 0 {
+            self.write.write_all(b"\n")?;
+            self.footer.draw(self.write, data)?;
+        }
+        Ok(())
+    }
+
+    fn clear_draw(&mut self) -> Result<()> {
+        self.write.clear();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// region: Swap constructors
+impl<'w, M1, H, F> StringRenderer<'w, M1, H, F> {
+    /// Swap out a margin formatter.
+    pub fn margin<M2>(self, margin: M2) -> StringRenderer<'w, M2, H, F> {
+        StringRenderer {
+            write: self.write,
+            margin,
+            header: self.header,
+            footer: self.footer,
+        }
+    }
+}
+
+impl<'w, M, H1, F> StringRenderer<'w, M, H1, F> {
+    /// Swap out a header formatter.
+    pub fn header<H2>(self, header: H2) -> StringRenderer<'w, M, H2, F> {
+        StringRenderer {
+            write: self.write,
+            margin: self.margin,
+            header,
+            footer: self.footer,
+        }
+    }
+}
+
+impl<'w, M, H, F1> StringRenderer<'w, M, H, F1> {
+    /// Swap out a footer formatter.
+    pub fn footer<F2>(self, footer: F2) -> StringRenderer<'w, M, H, F2> {
+        StringRenderer {
+            write: self.write,
+            margin: self.margin,
+            header: self.header,
+            footer,
+        }
+    }
+}
+
+impl<'w, M1, H1, F1> StringRenderer<'w, M1, H1, F1> {
+    /// Swap out the margin, header and footer formatters with a bundle.
+    pub fn style<M2, H2, F2>(
+        self,
+        style: StyleBundle<M2, H2, F2>,
+    ) -> StringRenderer<'w, M2, H2, F2> {
+        self.margin(style.margin)
+            .header(style.header)
+            .footer(style.footer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor::selection::Cursor, renderer::styles::classic, util::display_width};
+    use crossterm::style::{Attribute, Color, SetAttribute, SetBackgroundColor};
+
+    #[test]
+    fn draws_plain_lines() {
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out);
+        renderer.draw(&Editor::from("ab\ncd")).unwrap();
+        renderer.draw(&Editor::from("x\n\ny")).unwrap();
+        renderer.finish().unwrap();
+        // Each draw replaces the previous frame.
+        assert_eq!(String::from_utf8(out).unwrap(), "x\n\ny");
+    }
+
+    #[test]
+    fn draws_the_style() {
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out).style(classic::bundle("Title"));
+        renderer.draw(&Editor::from("ab\ncd")).unwrap();
+        renderer.finish().unwrap();
+        let frame = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = frame.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[0].contains("Title"));
+        assert!(rows[1].contains("1") && rows[1].ends_with("ab"));
+        assert!(rows[2].contains("2") && rows[2].ends_with("cd"));
+        assert!(rows[3].contains("Lines: 2"));
+    }
+
+    #[test]
+    fn clear_draw_empties_the_buffer() {
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out);
+        renderer.draw(&Editor::from("ab")).unwrap();
+        renderer.clear_draw().unwrap();
+        renderer.finish().unwrap();
+        assert!(out.is_empty());
+    }
+
+    /// Get the text drawn on the selection background of each line.
+    fn highlighted(frame: &str) -> Vec<String> {
+        let grey = format!("{}", SetBackgroundColor(Color::DarkGrey));
+        let bold = format!("{}", SetAttribute(Attribute::Bold));
+        frame
+            .lines()
+            .filter_map(|row| {
+                let (_, rest) = row.split_once(&grey)?;
+                let rest = rest.strip_prefix(&bold).unwrap_or(rest);
+                Some(rest[..rest.find('\x1b').unwrap_or(rest.len())].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selection_covers_the_cells_of_wide_characters() {
+        let mut editor = Editor::from("ab日本cd");
+        editor.set_selection(Cursor { ln: 0, col: 1 }, Cursor { ln: 0, col: 4 });
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out);
+        renderer.draw(&editor).unwrap();
+        renderer.finish().unwrap();
+        let selected = highlighted(&String::from_utf8(out).unwrap());
+        assert_eq!(selected, ["b日本"]);
+        assert_eq!(display_width(selected[0].chars(), true), 5);
+    }
+
+    #[test]
+    fn block_selection_covers_the_same_cells_on_every_line() {
+        let mut editor = Editor::from("ab日本cd\nabcdefg");
+        editor.set_selection(Cursor { ln: 1, col: 1 }, Cursor { ln: 0, col: 3 });
+        editor.selection.block = true;
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out);
+        renderer.draw(&editor).unwrap();
+        renderer.finish().unwrap();
+        let selected = highlighted(&String::from_utf8(out).unwrap());
+        assert_eq!(selected, ["b日", "bcd"]);
+        for text in selected {
+            assert_eq!(display_width(text.chars(), true), 3);
+        }
+    }
+
+    #[test]
+    fn rtl_lines_are_drawn_left_to_right() {
+        let mut editor = Editor::from("שלום abc\nabc\u{FEFF}");
+        editor.move_to_line_end(false);
+        let mut out = Vec::new();
+        let mut renderer = StringRenderer::render_to(&mut out);
+        renderer.draw(&editor).unwrap();
+        renderer.finish().unwrap();
+        // Only the line with strong right-to-left text is overridden,
+        // and a byte order mark is not right-to-left.
+        let frame = String::from_utf8(out).unwrap();
+        assert_eq!(frame, "\u{202D}שלום abc\u{202C}\nabc\u{FEFF}");
+
+        // The cursor is placed by the characters in logical order.
+        assert_eq!(editor.selection.focus, Cursor { ln: 0, col: 8 });
+        assert_eq!(editor.display_col(0, editor.selection.focus.col, true), 8);
+        assert_eq!(editor.display_col(0, 2, true), 2);
+    }
+}
@@ -1,52 +1,79 @@
 use std::io::Write;
 
-use super::{Footer, Header, Margin};
+use super::{
+    chars_label, number_width, status_marker, Footer, Header, Margin, Message, StyleBundle,
+};
 use crate::{renderer::Editor, Result};
 use crossterm::{
     terminal::{Clear, ClearType},
     QueueableCommand,
 };
 
-pub struct ClassicHeader<'s> {
-    pub message: &'s str
+/// The classic style, framing the content with box-drawing
+/// characters below a header with a message.
+pub fn bundle<M: Message>(
+    message: M,
+) -> StyleBundle<ClassicGutter, ClassicHeader<M>, ClassicFooter> {
+    StyleBundle::new()
+        .margin(ClassicGutter::default())
+        .header(ClassicHeader { message })
+        .footer(ClassicFooter)
+}
+
+/// Header with a message, which is either a string
+/// or a closure called with the editor on every draw.
+pub struct ClassicHeader<M> {
+    pub message: M,
 }
 
-impl<W: Write> Header<W> for ClassicHeader<'_> {
+impl<W: Write, M: Message> Header<W> for ClassicHeader<M> {
     fn rows(&self) -> usize {
         1
     }
 
-    fn draw(&mut self, w: &mut W, _: &Editor) -> Result<()> {
-        w.write("      ╭─── ".as_bytes())?;
-        w.write(self.message.as_bytes())?;
+    fn draw(&mut self, w: &mut W, data: &Editor) -> Result<()> {
+        w.write_all("      ╭─── ".as_bytes())?;
+        w.write_all(self.message.message(data).as_bytes())?;
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
 }
 
-pub struct ClassicGutter;
+#[derive(Default)]
+pub struct ClassicGutter {
+    /// Added to the line numbers, for content that does not
+    /// start at the first line.
+    pub line_offset: usize,
+}
 
 impl ClassicGutter {
-    const WIDTH: usize = 5;
     const PAD: usize = 3;
 
-    const DELIM: &'static str = " │ ";
-    const DELIM_BOLD: &'static str = " ┃ ";
+    const DELIM: &'static str = "│ ";
+    const DELIM_BOLD: &'static str = "┃ ";
+
+    /// Set the offset added to the line numbers.
+    pub fn with_line_offset(self, line_offset: usize) -> Self {
+        Self { line_offset }
+    }
 }
 
 impl<W: Write> Margin<W> for ClassicGutter {
-    fn width(&self) -> usize {
-        Self::WIDTH + Self::PAD
+    fn width(&self, data: &Editor) -> usize {
+        number_width(data, self.line_offset) + Self::PAD
     }
 
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        let width = number_width(data, self.line_offset);
         if line_idx + 1 > data.line_count() {
-            write!(write, "{:>width$}", "", width = Self::WIDTH)?;
+            write!(write, "{:>width$} ", "", width = width)?;
         } else {
-            write!(write, "{:>width$}", line_idx + 1, width = Self::WIDTH)?;
+            let number = line_idx + 1 + self.line_offset;
+            let marker = status_marker(data, line_idx);
+            write!(write, "{:>width$}{}", number, marker, width = width)?;
         }
 
-        write.write(
+        write.write_all(
             if line_idx == data.selection.focus.ln {
                 Self::DELIM_BOLD
             } else {
@@ -71,10 +98,15 @@ impl<W: Write> Footer<W> for ClassicFooter {
             w,
             "      ╰─── Lines: {} ─── Chars: {} ─── Ln: {}, Col: {}",
             data.line_count(),
-            data.char_count(),
+            chars_label(data),
             data.selection.focus.ln,
             data.selection.focus.col.min(data.curr_ln_len())
         )?;
+        if let Some(error) = data.validation_error() {
+            write!(w, " ─── {}", error)?;
+        } else if let Some(status) = data.status() {
+            write!(w, " ─── {}", status)?;
+        }
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
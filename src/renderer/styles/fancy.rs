@@ -1,78 +1,125 @@
-use super::{Footer, Header, Margin};
+use super::{
+    chars_label, number_width, status_marker, Footer, Header, Margin, Message, StyleBundle,
+};
 use crate::{renderer::Editor, Result};
 use crossterm::{
-    style::Colorize,
+    style::Stylize,
     terminal::{Clear, ClearType},
     QueueableCommand,
 };
 use std::io::Write;
 
-pub struct FancyHeader<'s> {
-    pub message: &'s str
+/// The fancy style, with shaded gutters and an info bar
+/// below a header with a message.
+pub fn bundle<M: Message>(message: M) -> StyleBundle<FancyGutter, FancyHeader<M>, FancyFooter> {
+    StyleBundle::new()
+        .margin(FancyGutter::default())
+        .header(FancyHeader { message })
+        .footer(FancyFooter)
+}
+
+/// Header with a message, which is either a string
+/// or a closure called with the editor on every draw.
+pub struct FancyHeader<M> {
+    pub message: M,
 }
 
-impl<W: Write> Header<W> for FancyHeader<'_> {
+impl<W: Write, M: Message> Header<W> for FancyHeader<M> {
     fn rows(&self) -> usize {
         1
     }
 
-    fn draw(&mut self, w: &mut W, _: &Editor) -> Result<()> {
+    fn draw(&mut self, w: &mut W, data: &Editor) -> Result<()> {
         write!(
             w,
             "{} {}",
             "       ".black().on_dark_grey(),
-            self.message
+            self.message.message(data)
         )?;
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
 }
 
-pub struct FancyGutter;
+#[derive(Default)]
+pub struct FancyGutter {
+    /// Show the distance from the line being edited
+    /// instead of absolute line numbers on other lines.
+    pub relative: bool,
+    /// Added to the line numbers, for content that does not
+    /// start at the first line.
+    pub line_offset: usize,
+}
 
 impl FancyGutter {
-    const MSG: &'static str = "Press enter to submit";
+    const MSG: &'static str = "Press Ctrl+D to submit";
+
+    /// Gutter with relative line numbers.
+    pub fn relative() -> Self {
+        Self {
+            relative: true,
+            ..Self::default()
+        }
+    }
+
+    /// Set the offset added to the line numbers.
+    pub fn with_line_offset(self, line_offset: usize) -> Self {
+        Self {
+            line_offset,
+            ..self
+        }
+    }
 }
 
 impl<W: Write> Margin<W> for FancyGutter {
-    fn width(&self) -> usize {
-        9
+    fn width(&self, data: &Editor) -> usize {
+        number_width(data, self.line_offset) + 4
     }
 
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        let digits = number_width(data, self.line_offset);
         if line_idx + 1 > data.line_count() {
             write!(
                 write,
                 "{}  ",
-                "       ".on_dark_grey()
+                format!("{:1$}", "", digits + 2).on_dark_grey()
             )?;
-        } else if line_idx + 1 == data.line_count() && data.line(line_idx).len() == 0 {
-            if line_idx == data.selection.focus.ln as usize {
+        } else if line_idx + 1 == data.line_count() && data.line(line_idx).is_empty() {
+            if line_idx == data.selection.focus.ln {
                 write!(
                     write,
                     "{} {}",
-                    "      ▶ ".black().on_green(),
+                    format!("{:>1$} ", "▶", digits + 2).black().on_green(),
                     Self::MSG.dark_grey()
                 )?;
             } else {
                 write!(
                     write,
                     "{}  {}",
-                    "     ▶ ".black().on_green(),
+                    format!("{:>1$} ", "▶", digits + 1).black().on_green(),
                     Self::MSG.dark_grey()
                 )?;
             }
-        } else if line_idx == data.selection.focus.ln as usize {
+        } else if line_idx == data.selection.focus.ln {
+            let number = line_idx + 1 + self.line_offset;
             write!(
                 write,
-                "{} ",
-                format!("  {:>5} ", line_idx + 1).black().on_dark_grey()
+                "{}{}",
+                format!("  {:>1$} ", number, digits).black().on_dark_grey(),
+                status_marker(data, line_idx)
             )?;
         } else {
+            let focus = data.selection.focus.ln;
+            let number = if self.relative {
+                line_idx.abs_diff(focus)
+            } else {
+                line_idx + 1 + self.line_offset
+            };
             write!(
                 write,
-                "{}  ",
-                format!(" {:>5} ", line_idx + 1).black().on_dark_grey()
+                "{} {}",
+                format!(" {:>1$} ", number, digits).black().on_dark_grey(),
+                status_marker(data, line_idx)
             )?;
         }
 
@@ -90,16 +137,18 @@ impl<W: Write> Footer<W> for FancyFooter {
     fn draw(&mut self, w: &mut W, data: &Editor) -> Result<()> {
         write!(
             w,
-            "{}{}{}{}",
+            "{} Lines: {:>3}  Chars: {:>3}  Ln {}, Col {} ",
             "  info ".black().on_dark_grey(),
-            format!(" Lines: {:>3} ", data.line_count()),
-            format!(" Chars: {:>3} ", data.char_count()),
-            format!(
-                " Ln {}, Col {} ",
-                data.selection.focus.ln,
-                data.selection.focus.col.min(data.curr_ln().len())
-            )
+            data.line_count(),
+            chars_label(data),
+            data.selection.focus.ln,
+            data.selection.focus.col.min(data.curr_ln().len())
         )?;
+        if let Some(error) = data.validation_error() {
+            write!(w, " {}", error.red())?;
+        } else if let Some(status) = data.status() {
+            write!(w, " {}", status.dark_grey())?;
+        }
 
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
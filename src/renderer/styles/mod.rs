@@ -1,15 +1,98 @@
-use std::io::Write;
+use std::{borrow::Cow, io::Write, ops::Range};
 
 use super::Editor;
-use crate::Result;
+use crate::{editor::LineStatus, Result};
+
+use crossterm::style::{Color, StyledContent, Stylize};
 
 pub mod classic;
 pub mod fancy;
 
-pub struct Style<'w, W> {
-    pub header: &'w dyn Header<W>,
-    pub margin: &'w dyn Margin<W>,
-    pub footer: &'w dyn Footer<W>
+/// A margin, header and footer that are swapped into a renderer together.
+///
+/// The parts are independent, so parts of different styles can be mixed,
+/// such as a fancy gutter with a classic footer.
+pub struct StyleBundle<M, H, F> {
+    pub margin: M,
+    pub header: H,
+    pub footer: F,
+}
+
+impl StyleBundle<NoStyle, NoStyle, NoStyle> {
+    /// Create a bundle without any margin, header or footer.
+    pub fn new() -> Self {
+        StyleBundle {
+            margin: NoStyle,
+            header: NoStyle,
+            footer: NoStyle,
+        }
+    }
+}
+
+impl Default for StyleBundle<NoStyle, NoStyle, NoStyle> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M1, H, F> StyleBundle<M1, H, F> {
+    /// Swap out the margin formatter.
+    pub fn margin<M2>(self, margin: M2) -> StyleBundle<M2, H, F> {
+        StyleBundle {
+            margin,
+            header: self.header,
+            footer: self.footer,
+        }
+    }
+}
+
+impl<M, H1, F> StyleBundle<M, H1, F> {
+    /// Swap out the header formatter.
+    pub fn header<H2>(self, header: H2) -> StyleBundle<M, H2, F> {
+        StyleBundle {
+            margin: self.margin,
+            header,
+            footer: self.footer,
+        }
+    }
+}
+
+impl<M, H, F1> StyleBundle<M, H, F1> {
+    /// Swap out the footer formatter.
+    pub fn footer<F2>(self, footer: F2) -> StyleBundle<M, H, F2> {
+        StyleBundle {
+            margin: self.margin,
+            header: self.header,
+            footer,
+        }
+    }
+}
+
+/// Message shown by a header, either fixed or computed
+/// from the editor on every draw.
+pub trait Message {
+    fn message(&self, data: &Editor) -> Cow<'_, str>;
+}
+
+impl Message for &str {
+    fn message(&self, _: &Editor) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Message for String {
+    fn message(&self, _: &Editor) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T> Message for T
+where
+    T: Fn(&Editor) -> String,
+{
+    fn message(&self, data: &Editor) -> Cow<'_, str> {
+        Cow::Owned(self(data))
+    }
 }
 
 pub trait Header<W> {
@@ -39,13 +122,15 @@ impl<W: Write> Header<W> for Box<dyn Header<W>> {
 }
 
 pub trait Margin<W> {
-    fn width(&self) -> usize;
+    /// Get the width of the margin, which may depend on the
+    /// editor, such as to fit the number of the last line.
+    fn width(&self, data: &Editor) -> usize;
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()>;
 }
 
 impl<W: Write> Margin<W> for Box<dyn Margin<W>> {
-    fn width(&self) -> usize {
-        (**self).width()
+    fn width(&self, data: &Editor) -> usize {
+        (**self).width(data)
     }
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
         (**self).draw(write, line_idx, data)
@@ -53,7 +138,7 @@ impl<W: Write> Margin<W> for Box<dyn Margin<W>> {
 }
 
 impl<W> Margin<W> for NoStyle {
-    fn width(&self) -> usize {
+    fn width(&self, _: &Editor) -> usize {
         0
     }
 
@@ -62,6 +147,34 @@ impl<W> Margin<W> for NoStyle {
     }
 }
 
+/// Get the width of the line numbers of a gutter, which is at least 5,
+/// to line up with the header and footer, and grows to fit the number
+/// of the last line.
+fn number_width(data: &Editor, line_offset: usize) -> usize {
+    (data.line_count() + line_offset).to_string().len().max(5)
+}
+
+/// Get the marker shown in gutters for how a line differs from the
+/// baseline of the editor, which is a space for unchanged lines.
+fn status_marker(data: &Editor, line_idx: usize) -> StyledContent<char> {
+    match data.line_status(line_idx) {
+        LineStatus::Unchanged => ' '.stylize(),
+        LineStatus::Added => '+'.green(),
+        LineStatus::Modified => '~'.yellow(),
+        LineStatus::RemovedAbove => '-'.red(),
+    }
+}
+
+/// Get the character count shown in footers, counting graphemes,
+/// or the characters along with the maximum if there is one,
+/// since the maximum counts characters.
+fn chars_label(data: &Editor) -> String {
+    match data.max_chars() {
+        Some(max) => format!("{}/{}", data.char_count(), max),
+        None => data.grapheme_count().to_string(),
+    }
+}
+
 pub trait Footer<W> {
     fn rows(&self) -> usize;
     fn draw(&mut self, write: &mut W, data: &Editor) -> Result<()>;
@@ -85,3 +198,75 @@ impl<W> Footer<W> for NoStyle {
         Ok(())
     }
 }
+
+/// Non-editable text rendered after the content of a line,
+/// such as inline type hints or annotations.
+pub trait InlineSuffix {
+    fn suffix(&self, line_idx: usize, data: &Editor) -> Option<(String, Color)>;
+}
+
+impl<T> InlineSuffix for T
+where
+    T: Fn(usize, &Editor) -> Option<(String, Color)>,
+{
+    fn suffix(&self, line_idx: usize, data: &Editor) -> Option<(String, Color)> {
+        self(line_idx, data)
+    }
+}
+
+/// Colors the text of a line, such as for syntax highlighting.
+pub trait Highlighter {
+    /// Get the foreground color of character column ranges of a line.
+    /// Later spans take precedence over earlier ones.
+    fn spans(&self, line: &str) -> Vec<(Range<usize>, Color)>;
+}
+
+impl<T> Highlighter for T
+where
+    T: Fn(&str) -> Vec<(Range<usize>, Color)>,
+{
+    fn spans(&self, line: &str) -> Vec<(Range<usize>, Color)> {
+        self(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use classic::ClassicGutter;
+    use fancy::FancyGutter;
+
+    fn numbered(lines: usize) -> Editor {
+        let text: Vec<String> = (0..lines).map(|ln| ln.to_string()).collect();
+        Editor::from(text.join("\n").as_str())
+    }
+
+    #[test]
+    fn gutter_width_fits_a_thousand_lines() {
+        let editor = numbered(1000);
+        assert_eq!(number_width(&editor, 0), 5);
+        assert_eq!(
+            Margin::<Vec<u8>>::width(&ClassicGutter::default(), &editor),
+            8
+        );
+        assert_eq!(
+            Margin::<Vec<u8>>::width(&FancyGutter::default(), &editor),
+            9
+        );
+
+        let mut out = Vec::new();
+        ClassicGutter::default()
+            .draw(&mut out, 999, &editor)
+            .unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with(" 1000"));
+    }
+
+    #[test]
+    fn gutter_width_grows_past_five_digits() {
+        let editor = numbered(1000);
+        assert_eq!(number_width(&editor, 99_000), 6);
+        let gutter = ClassicGutter::default().with_line_offset(99_000);
+        assert_eq!(Margin::<Vec<u8>>::width(&gutter, &editor), 9);
+        assert_eq!(number_width(&numbered(100_000), 0), 6);
+    }
+}
@@ -1,4 +1,5 @@
 use ropey::RopeSlice;
+use unicode_width::UnicodeWidthChar;
  
 pub(crate) fn trimmed(rope: RopeSlice) -> RopeSlice {
     let rope_len = rope.len_chars();
@@ -10,3 +11,80 @@ pub(crate) fn trimmed(rope: RopeSlice) -> RopeSlice {
         rope
     }
 }
+
+/// Check if a character has a strong right-to-left direction.
+pub(crate) fn is_rtl(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFC}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+/// Encode bytes as standard base64 with padding.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.chunks(3).len() * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Get the number of terminal cells the characters occupy.
+pub(crate) fn display_width(chars: impl Iterator<Item = char>, escape: bool) -> usize {
+    chars.map(|c| char_width(c, escape)).sum()
+}
+
+/// Get the number of terminal cells a character occupies, drawing
+/// control characters with `control_repr` if `escape`, or else raw,
+/// which takes up no cells.
+pub(crate) fn char_width(c: char, escape: bool) -> usize {
+    match c {
+        c if c.is_control() && !escape => 0,
+        '\u{0}'..='\u{1F}' | '\u{7F}' => 2,
+        c if c.is_control() => 1,
+        c => c.width().unwrap_or(0),
+    }
+}
+
+/// Get the visible form of a control character, in caret notation
+/// such as `^A`, or as the replacement character if it has none.
+pub(crate) fn control_repr(c: char) -> Option<String> {
+    match c {
+        '\u{0}'..='\u{1F}' => Some(format!("^{}", (c as u8 + 0x40) as char)),
+        '\u{7F}' => Some("^?".to_string()),
+        c if c.is_control() => Some("\u{FFFD}".to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_the_rfc_4648_vectors() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}